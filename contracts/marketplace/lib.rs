@@ -9,22 +9,40 @@ pub mod marketplace {
             Env,
         },
         env::DefaultEnvironment,
+        prelude::vec::Vec,
         EnvAccess,
     };
     use openbrush::{
         contracts::{
             ownable::*,
-            psp34::Id,
+            psp34::{
+                Id,
+                PSP34Receiver,
+                PSP34ReceiverError,
+            },
             reentrancy_guard::*,
         },
-        traits::Storage,
+        traits::{
+            Storage,
+            String,
+        },
     };
     use pallet_marketplace::{
         impls::marketplace::{
+            auction::AuctionEvents,
+            bundle::BundleEvents,
             marketplace_sale::MarketplaceSaleEvents,
+            offer::OfferEvents,
+            swap::SwapEvents,
             *,
         },
-        traits::marketplace::*,
+        traits::{
+            auction::*,
+            bundle::*,
+            marketplace::*,
+            offer::*,
+            swap::*,
+        },
     };
 
     // MarketplaceContract contract storage
@@ -48,6 +66,8 @@ pub mod marketplace {
         id: Id,
         #[ink(topic)]
         price: Option<Balance>,
+        currency: Option<AccountId>,
+        reserved_for: Option<AccountId>,
     }
 
     /// Event emitted when a token is bought
@@ -59,6 +79,31 @@ pub mod marketplace {
         id: Id,
         #[ink(topic)]
         price: Balance,
+        currency: Option<AccountId>,
+    }
+
+    /// Event emitted when price of a listed token is updated
+    #[ink(event)]
+    pub struct ListingPriceUpdated {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        old_price: Balance,
+        new_price: Balance,
+    }
+
+    /// Event emitted when token is listed with a declining price
+    #[ink(event)]
+    pub struct DutchAuctionListed {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        start_price: Balance,
+        end_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
     }
 
     /// Event emitted when a NFT contract is registered to the marketplace.
@@ -68,12 +113,282 @@ pub mod marketplace {
         contract: AccountId,
     }
 
+    /// Event emitted when a marketplace fee or fee recipient change is proposed.
+    #[ink(event)]
+    pub struct FeeChangeProposed {
+        fee: Option<u16>,
+        #[ink(topic)]
+        fee_recipient: Option<AccountId>,
+        executable_at: Timestamp,
+    }
+
+    /// Event emitted when a marketplace fee or fee recipient is changed.
+    #[ink(event)]
+    pub struct FeeChangeExecuted {
+        fee: Option<u16>,
+        #[ink(topic)]
+        fee_recipient: Option<AccountId>,
+    }
+
+    /// Event emitted when a proposed marketplace fee or fee recipient change is cancelled.
+    #[ink(event)]
+    pub struct FeeChangeCancelled {
+        fee: Option<u16>,
+        #[ink(topic)]
+        fee_recipient: Option<AccountId>,
+    }
+
+    /// Event emitted when royalty of a single token is set or removed.
+    #[ink(event)]
+    pub struct TokenRoyaltySet {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        royalty: Option<u16>,
+    }
+
+    /// Event emitted when marketplace fee of a collection is set or removed.
+    #[ink(event)]
+    pub struct CollectionFeeSet {
+        #[ink(topic)]
+        contract: AccountId,
+        fee: Option<u16>,
+    }
+
+    /// Event emitted when a collection marketplace fee increase is proposed.
+    #[ink(event)]
+    pub struct CollectionFeeChangeProposed {
+        #[ink(topic)]
+        contract: AccountId,
+        fee: Option<u16>,
+        executable_at: Timestamp,
+    }
+
+    /// Event emitted when a proposed collection marketplace fee change is cancelled.
+    #[ink(event)]
+    pub struct CollectionFeeChangeCancelled {
+        #[ink(topic)]
+        contract: AccountId,
+        fee: Option<u16>,
+    }
+
+    /// Event emitted when a stale listing is removed
+    #[ink(event)]
+    pub struct ListingInvalidated {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        #[ink(topic)]
+        owner: AccountId,
+    }
+
+    /// Event emitted when pending proceeds are withdrawn
+    #[ink(event)]
+    pub struct ProceedsWithdrawn {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when a token is put up for an auction
+    #[ink(event)]
+    pub struct AuctionCreated {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        reserve_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    }
+
+    /// Event emitted when a bid is placed on an auctioned token
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when an auction is settled
+    #[ink(event)]
+    pub struct AuctionSettled {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        price: Balance,
+    }
+
+    /// Event emitted when an auction is cancelled
+    #[ink(event)]
+    pub struct AuctionCancelled {
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+    }
+
+    /// Event emitted when a token is put up for a sealed-bid auction
+    #[ink(event)]
+    pub struct SealedAuctionCreated {
+        #[ink(topic)]
+        auction_id: u64,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        reserve_price: Balance,
+        commit_end: Timestamp,
+        reveal_end: Timestamp,
+        second_price: bool,
+    }
+
+    /// Event emitted when a sealed bid is committed
+    #[ink(event)]
+    pub struct SealedBidCommitted {
+        #[ink(topic)]
+        auction_id: u64,
+        #[ink(topic)]
+        bidder: AccountId,
+    }
+
+    /// Event emitted when a sealed bid is revealed
+    #[ink(event)]
+    pub struct SealedBidRevealed {
+        #[ink(topic)]
+        auction_id: u64,
+        #[ink(topic)]
+        bidder: AccountId,
+        bid: Balance,
+    }
+
+    /// Event emitted when an offer is made for a token
+    #[ink(event)]
+    pub struct OfferMade {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        contract: AccountId,
+        #[ink(topic)]
+        id: Id,
+        buyer: AccountId,
+        price: Balance,
+        expiry: Timestamp,
+    }
+
+    /// Event emitted when an offer is cancelled
+    #[ink(event)]
+    pub struct OfferCancelled {
+        #[ink(topic)]
+        offer_id: u64,
+    }
+
+    /// Event emitted when an offer is accepted
+    #[ink(event)]
+    pub struct OfferAccepted {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        seller: AccountId,
+    }
+
+    /// Event emitted when an offer is made for any token from a collection
+    #[ink(event)]
+    pub struct CollectionOfferMade {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        contract: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+        attribute: Option<(String, String)>,
+    }
+
+    /// Event emitted when a token is sold to a collection offer
+    #[ink(event)]
+    pub struct CollectionOfferAccepted {
+        #[ink(topic)]
+        offer_id: u64,
+        #[ink(topic)]
+        id: Id,
+        #[ink(topic)]
+        seller: AccountId,
+    }
+
+    /// Event emitted when a bundle of tokens is listed
+    #[ink(event)]
+    pub struct BundleListed {
+        #[ink(topic)]
+        bundle_id: u64,
+        #[ink(topic)]
+        owner: AccountId,
+        price: Balance,
+    }
+
+    /// Event emitted when a bundle is unlisted
+    #[ink(event)]
+    pub struct BundleUnlisted {
+        #[ink(topic)]
+        bundle_id: u64,
+    }
+
+    /// Event emitted when a bundle is bought
+    #[ink(event)]
+    pub struct BundleBought {
+        #[ink(topic)]
+        bundle_id: u64,
+        #[ink(topic)]
+        buyer: AccountId,
+        price: Balance,
+    }
+
+    /// Event emitted when a swap order is made
+    #[ink(event)]
+    pub struct SwapMade {
+        #[ink(topic)]
+        swap_id: u64,
+        #[ink(topic)]
+        maker: AccountId,
+        top_up: Balance,
+        expiry: Timestamp,
+    }
+
+    /// Event emitted when a swap order is cancelled
+    #[ink(event)]
+    pub struct SwapCancelled {
+        #[ink(topic)]
+        swap_id: u64,
+    }
+
+    /// Event emitted when a swap order is executed
+    #[ink(event)]
+    pub struct SwapAccepted {
+        #[ink(topic)]
+        swap_id: u64,
+        #[ink(topic)]
+        taker: AccountId,
+    }
+
     impl MarketplaceContract {
         #[ink(constructor)]
         pub fn new(market_fee_recipient: AccountId) -> Self {
             let mut instance = Self::default();
             instance.marketplace.fee = 100; // 1%
             instance.marketplace.max_fee = 1000; // 10%
+            instance.marketplace.fee_change_delay = types::DEFAULT_FEE_CHANGE_DELAY;
             instance.marketplace.market_fee_recipient = Option::Some(market_fee_recipient);
 
             let caller = instance.env().caller();
@@ -88,6 +403,8 @@ pub mod marketplace {
             contract: AccountId,
             token_id: Id,
             price: Option<Balance>,
+            currency: Option<AccountId>,
+            reserved_for: Option<AccountId>,
         ) {
             <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
                 TokenListed,
@@ -97,11 +414,19 @@ pub mod marketplace {
                     contract,
                     id: token_id,
                     price,
+                    currency,
+                    reserved_for,
                 },
             );
         }
 
-        fn emit_token_bought_event(&self, contract: AccountId, token_id: Id, price: Balance) {
+        fn emit_token_bought_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            price: Balance,
+            currency: Option<AccountId>,
+        ) {
             <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
                 TokenBought,
             >(
@@ -110,6 +435,51 @@ pub mod marketplace {
                     contract,
                     id: token_id,
                     price,
+                    currency,
+                },
+            );
+        }
+
+        fn emit_listing_price_updated_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            old_price: Balance,
+            new_price: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingPriceUpdated,
+            >(
+                self.env(),
+                ListingPriceUpdated {
+                    contract,
+                    id: token_id,
+                    old_price,
+                    new_price,
+                },
+            );
+        }
+
+        fn emit_dutch_auction_listed_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            start_price: Balance,
+            end_price: Balance,
+            start_time: Timestamp,
+            end_time: Timestamp,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                DutchAuctionListed,
+            >(
+                self.env(),
+                DutchAuctionListed {
+                    contract,
+                    id: token_id,
+                    start_price,
+                    end_price,
+                    start_time,
+                    end_time,
                 },
             );
         }
@@ -119,198 +489,2566 @@ pub mod marketplace {
                 CollectionRegistered,
             >(self.env(), CollectionRegistered { contract })
         }
-    }
 
-    impl MarketplaceSale for MarketplaceContract {}
+        fn emit_collection_fee_set_event(&self, contract: AccountId, fee: Option<u16>) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionFeeSet,
+            >(self.env(), CollectionFeeSet { contract, fee })
+        }
 
-    // ***************************** Tests *******************************
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-        use crate::marketplace::MarketplaceContract;
-        use ink::env::test;
-        use openbrush::{
-            contracts::psp34::Id,
-            traits::String,
-        };
-        use pallet_marketplace::impls::marketplace::types::{
-            MarketplaceError,
-            NftContractType,
-        };
+        fn emit_collection_fee_change_proposed_event(
+            &self,
+            contract: AccountId,
+            fee: Option<u16>,
+            executable_at: Timestamp,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionFeeChangeProposed,
+            >(
+                self.env(),
+                CollectionFeeChangeProposed {
+                    contract,
+                    fee,
+                    executable_at,
+                },
+            );
+        }
 
-        #[ink::test]
-        fn new_works() {
-            let marketplace = init_contract();
-            assert_eq!(marketplace.get_marketplace_fee(), 100);
-            assert_eq!(marketplace.get_max_fee(), 1000);
-            assert_eq!(marketplace.get_fee_recipient(), fee_recipient());
+        fn emit_collection_fee_change_cancelled_event(
+            &self,
+            contract: AccountId,
+            fee: Option<u16>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionFeeChangeCancelled,
+            >(self.env(), CollectionFeeChangeCancelled { contract, fee })
         }
 
-        #[ink::test]
-        fn set_marketplace_fee_works() {
-            let mut marketplace = init_contract();
+        fn emit_fee_change_proposed_event(
+            &self,
+            fee: Option<u16>,
+            fee_recipient: Option<AccountId>,
+            executable_at: Timestamp,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FeeChangeProposed,
+            >(
+                self.env(),
+                FeeChangeProposed {
+                    fee,
+                    fee_recipient,
+                    executable_at,
+                },
+            );
+        }
 
-            assert!(marketplace.set_marketplace_fee(120).is_ok());
-            assert_eq!(marketplace.get_marketplace_fee(), 120);
+        fn emit_fee_change_executed_event(
+            &self,
+            fee: Option<u16>,
+            fee_recipient: Option<AccountId>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FeeChangeExecuted,
+            >(self.env(), FeeChangeExecuted { fee, fee_recipient })
         }
 
-        #[ink::test]
-        fn set_marketplace_fee_fails_if_not_owner() {
-            let mut marketplace = init_contract();
+        fn emit_fee_change_cancelled_event(
+            &self,
+            fee: Option<u16>,
+            fee_recipient: Option<AccountId>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                FeeChangeCancelled,
+            >(self.env(), FeeChangeCancelled { fee, fee_recipient })
+        }
+
+        fn emit_token_royalty_set_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            royalty: Option<u16>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                TokenRoyaltySet,
+            >(
+                self.env(),
+                TokenRoyaltySet {
+                    contract,
+                    id: token_id,
+                    royalty,
+                },
+            );
+        }
+
+        fn emit_listing_invalidated_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            owner: AccountId,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ListingInvalidated,
+            >(
+                self.env(),
+                ListingInvalidated {
+                    contract,
+                    id: token_id,
+                    owner,
+                },
+            );
+        }
+
+        fn emit_proceeds_withdrawn_event(
+            &self,
+            account: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                ProceedsWithdrawn,
+            >(
+                self.env(),
+                ProceedsWithdrawn {
+                    account,
+                    to,
+                    amount,
+                },
+            );
+        }
+    }
+
+    impl AuctionEvents for MarketplaceContract {
+        fn emit_auction_created_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            reserve_price: Balance,
+            start_time: Timestamp,
+            end_time: Timestamp,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionCreated,
+            >(
+                self.env(),
+                AuctionCreated {
+                    contract,
+                    id: token_id,
+                    reserve_price,
+                    start_time,
+                    end_time,
+                },
+            );
+        }
+
+        fn emit_bid_placed_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            bidder: AccountId,
+            amount: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BidPlaced,
+            >(
+                self.env(),
+                BidPlaced {
+                    contract,
+                    id: token_id,
+                    bidder,
+                    amount,
+                },
+            );
+        }
+
+        fn emit_auction_settled_event(
+            &self,
+            contract: AccountId,
+            token_id: Id,
+            winner: Option<AccountId>,
+            price: Balance,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionSettled,
+            >(
+                self.env(),
+                AuctionSettled {
+                    contract,
+                    id: token_id,
+                    winner,
+                    price,
+                },
+            );
+        }
+
+        fn emit_auction_cancelled_event(&self, contract: AccountId, token_id: Id) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                AuctionCancelled,
+            >(
+                self.env(),
+                AuctionCancelled {
+                    contract,
+                    id: token_id,
+                },
+            )
+        }
+
+        fn emit_sealed_auction_created_event(
+            &self,
+            auction_id: u64,
+            contract: AccountId,
+            token_id: Id,
+            reserve_price: Balance,
+            commit_end: Timestamp,
+            reveal_end: Timestamp,
+            second_price: bool,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SealedAuctionCreated,
+            >(
+                self.env(),
+                SealedAuctionCreated {
+                    auction_id,
+                    contract,
+                    id: token_id,
+                    reserve_price,
+                    commit_end,
+                    reveal_end,
+                    second_price,
+                },
+            )
+        }
+
+        fn emit_sealed_bid_committed_event(&self, auction_id: u64, bidder: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SealedBidCommitted,
+            >(self.env(), SealedBidCommitted { auction_id, bidder })
+        }
+
+        fn emit_sealed_bid_revealed_event(&self, auction_id: u64, bidder: AccountId, bid: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SealedBidRevealed,
+            >(
+                self.env(),
+                SealedBidRevealed {
+                    auction_id,
+                    bidder,
+                    bid,
+                },
+            )
+        }
+    }
+
+    impl OfferEvents for MarketplaceContract {
+        fn emit_offer_made_event(
+            &self,
+            offer_id: u64,
+            contract: AccountId,
+            token_id: Id,
+            buyer: AccountId,
+            price: Balance,
+            expiry: Timestamp,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                OfferMade,
+            >(
+                self.env(),
+                OfferMade {
+                    offer_id,
+                    contract,
+                    id: token_id,
+                    buyer,
+                    price,
+                    expiry,
+                },
+            );
+        }
+
+        fn emit_offer_cancelled_event(&self, offer_id: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                OfferCancelled,
+            >(self.env(), OfferCancelled { offer_id })
+        }
+
+        fn emit_offer_accepted_event(&self, offer_id: u64, seller: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                OfferAccepted,
+            >(self.env(), OfferAccepted { offer_id, seller })
+        }
+
+        fn emit_collection_offer_made_event(
+            &self,
+            offer_id: u64,
+            contract: AccountId,
+            buyer: AccountId,
+            price: Balance,
+            quantity: u32,
+            expiry: Timestamp,
+            attribute: Option<(String, String)>,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionOfferMade,
+            >(
+                self.env(),
+                CollectionOfferMade {
+                    offer_id,
+                    contract,
+                    buyer,
+                    price,
+                    quantity,
+                    expiry,
+                    attribute,
+                },
+            );
+        }
+
+        fn emit_collection_offer_accepted_event(
+            &self,
+            offer_id: u64,
+            token_id: Id,
+            seller: AccountId,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                CollectionOfferAccepted,
+            >(
+                self.env(),
+                CollectionOfferAccepted {
+                    offer_id,
+                    id: token_id,
+                    seller,
+                },
+            );
+        }
+    }
+
+    impl BundleEvents for MarketplaceContract {
+        fn emit_bundle_listed_event(&self, bundle_id: u64, owner: AccountId, price: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BundleListed,
+            >(
+                self.env(),
+                BundleListed {
+                    bundle_id,
+                    owner,
+                    price,
+                },
+            );
+        }
+
+        fn emit_bundle_unlisted_event(&self, bundle_id: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BundleUnlisted,
+            >(self.env(), BundleUnlisted { bundle_id })
+        }
+
+        fn emit_bundle_bought_event(&self, bundle_id: u64, buyer: AccountId, price: Balance) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                BundleBought,
+            >(
+                self.env(),
+                BundleBought {
+                    bundle_id,
+                    buyer,
+                    price,
+                },
+            );
+        }
+    }
+
+    impl SwapEvents for MarketplaceContract {
+        fn emit_swap_made_event(
+            &self,
+            swap_id: u64,
+            maker: AccountId,
+            top_up: Balance,
+            expiry: Timestamp,
+        ) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SwapMade,
+            >(
+                self.env(),
+                SwapMade {
+                    swap_id,
+                    maker,
+                    top_up,
+                    expiry,
+                },
+            );
+        }
+
+        fn emit_swap_cancelled_event(&self, swap_id: u64) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SwapCancelled,
+            >(self.env(), SwapCancelled { swap_id })
+        }
+
+        fn emit_swap_accepted_event(&self, swap_id: u64, taker: AccountId) {
+            <EnvAccess<'_, DefaultEnvironment> as EmitEvent<MarketplaceContract>>::emit_event::<
+                SwapAccepted,
+            >(self.env(), SwapAccepted { swap_id, taker })
+        }
+    }
+
+    impl MarketplaceSale for MarketplaceContract {}
+
+    impl MarketplaceAuction for MarketplaceContract {}
+
+    impl MarketplaceOffer for MarketplaceContract {}
+
+    impl MarketplaceBundle for MarketplaceContract {}
+
+    impl MarketplaceSwap for MarketplaceContract {}
+
+    impl PSP34Receiver for MarketplaceContract {
+        /// Accepts only tokens transferred into escrow by the marketplace itself.
+        #[ink(message)]
+        fn before_received(
+            &mut self,
+            operator: AccountId,
+            _from: AccountId,
+            _id: Id,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP34ReceiverError> {
+            if operator != self.env().account_id() {
+                return Err(PSP34ReceiverError::TransferRejected(String::from(
+                    "Only escrow transfers are accepted",
+                )))
+            }
+            Ok(())
+        }
+    }
+
+    // ***************************** Tests *******************************
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::marketplace::MarketplaceContract;
+        use ink::env::{
+            hash::{
+                Blake2x256,
+                HashOutput,
+            },
+            test,
+        };
+        use openbrush::{
+            contracts::psp34::Id,
+            traits::String,
+        };
+        use pallet_marketplace::impls::marketplace::{
+            bundle::BundleInternal,
+            marketplace_sale::Internal,
+            types::{
+                Auction,
+                Bundle,
+                DutchAuction,
+                Item,
+                ListingOptions,
+                MarketplaceError,
+                NftContractType,
+                SealedAuction,
+                Swap,
+                SwapTarget,
+                DEFAULT_FEE_CHANGE_DELAY,
+                MAX_COLLECTION_OFFERS,
+                MAX_OFFERS_PER_TOKEN,
+            },
+        };
+
+        #[ink::test]
+        fn new_works() {
+            let marketplace = init_contract();
+            assert_eq!(marketplace.get_marketplace_fee(), 100);
+            assert_eq!(marketplace.get_max_fee(), 1000);
+            assert_eq!(marketplace.get_fee_recipient(), fee_recipient());
+            assert_eq!(marketplace.get_fee_change_delay(), DEFAULT_FEE_CHANGE_DELAY);
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_works() {
+            let mut marketplace = init_contract();
+
+            assert!(marketplace.propose_marketplace_fee(120).is_ok());
+            assert_eq!(
+                marketplace.execute_marketplace_fee(),
+                Err(MarketplaceError::FeeChangeNotReady)
+            );
+            test::set_block_timestamp::<Environment>(DEFAULT_FEE_CHANGE_DELAY);
+            assert!(marketplace.execute_marketplace_fee().is_ok());
+            assert_eq!(marketplace.get_marketplace_fee(), 120);
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.propose_marketplace_fee(120),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+            assert_eq!(
+                marketplace.execute_marketplace_fee(),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_marketplace_fee_fails_if_fee_too_high() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.propose_marketplace_fee(1001),
+                Err(MarketplaceError::FeeTooHigh)
+            );
+            assert!(marketplace.propose_marketplace_fee(1000).is_ok());
+        }
+
+        #[ink::test]
+        fn marketplace_fee_increase_waits_for_delay() {
+            let mut marketplace = init_contract();
+            marketplace.marketplace.fee_change_delay = 10;
+            assert_eq!(
+                marketplace.execute_marketplace_fee(),
+                Err(MarketplaceError::NoPendingFeeChange)
+            );
+
+            assert!(marketplace.propose_marketplace_fee(120).is_ok());
+            assert_eq!(marketplace.get_pending_marketplace_fee(), Some((120, 10)));
+            assert_eq!(
+                marketplace.execute_marketplace_fee(),
+                Err(MarketplaceError::FeeChangeNotReady)
+            );
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert!(marketplace.execute_marketplace_fee().is_ok());
+            assert_eq!(marketplace.get_marketplace_fee(), 120);
+            assert_eq!(marketplace.get_pending_marketplace_fee(), None);
+            assert_eq!(2, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn marketplace_fee_decrease_bypasses_delay() {
+            let mut marketplace = init_contract();
+            marketplace.marketplace.fee_change_delay = 10;
+
+            assert!(marketplace.propose_marketplace_fee(50).is_ok());
+            assert_eq!(marketplace.get_marketplace_fee(), 50);
+            assert_eq!(marketplace.get_pending_marketplace_fee(), None);
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn marketplace_fee_decrease_cancels_pending_increase() {
+            let mut marketplace = init_contract();
+            marketplace.marketplace.fee_change_delay = 10;
+
+            assert!(marketplace.propose_marketplace_fee(120).is_ok());
+            assert!(marketplace.propose_marketplace_fee(50).is_ok());
+            assert_eq!(marketplace.get_pending_marketplace_fee(), None);
+            // Proposed, cancelled and executed events.
+            assert_eq!(3, ink::env::test::recorded_events().count());
+
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.execute_marketplace_fee(),
+                Err(MarketplaceError::NoPendingFeeChange)
+            );
+            assert_eq!(marketplace.get_marketplace_fee(), 50);
+        }
+
+        #[ink::test]
+        fn cancel_marketplace_fee_works() {
+            let mut marketplace = init_contract();
+            marketplace.marketplace.fee_change_delay = 10;
+            assert_eq!(
+                marketplace.cancel_marketplace_fee(),
+                Err(MarketplaceError::NoPendingFeeChange)
+            );
+
+            assert!(marketplace.propose_marketplace_fee(120).is_ok());
+            assert!(marketplace.cancel_marketplace_fee().is_ok());
+            assert_eq!(marketplace.get_pending_marketplace_fee(), None);
+            assert_eq!(marketplace.get_marketplace_fee(), 100);
+            assert_eq!(2, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn set_fee_change_delay_only_increases() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.set_fee_change_delay(DEFAULT_FEE_CHANGE_DELAY - 1),
+                Err(MarketplaceError::InvalidFeeChangeDelay)
+            );
+            assert!(marketplace
+                .set_fee_change_delay(DEFAULT_FEE_CHANGE_DELAY + 10)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_fee_change_delay(),
+                DEFAULT_FEE_CHANGE_DELAY + 10
+            );
+            assert_eq!(
+                marketplace.set_fee_change_delay(30 * 24 * 60 * 60 * 1000 + 1),
+                Err(MarketplaceError::InvalidFeeChangeDelay)
+            );
+        }
+
+        #[ink::test]
+        fn set_fee_recipient_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.fee_change_delay = 10;
+
+            assert!(marketplace.propose_fee_recipient(accounts.bob).is_ok());
+            assert_eq!(
+                marketplace.get_pending_fee_recipient(),
+                Some((accounts.bob, 10))
+            );
+            assert_eq!(
+                marketplace.execute_fee_recipient(),
+                Err(MarketplaceError::FeeChangeNotReady)
+            );
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert!(marketplace.execute_fee_recipient().is_ok());
+            assert_eq!(marketplace.get_fee_recipient(), accounts.bob);
+            assert_eq!(marketplace.get_pending_fee_recipient(), None);
+
+            assert!(marketplace.propose_fee_recipient(accounts.charlie).is_ok());
+            assert!(marketplace.cancel_fee_recipient().is_ok());
+            assert_eq!(marketplace.get_fee_recipient(), accounts.bob);
+            assert_eq!(4, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn set_fee_recipient_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.propose_fee_recipient(accounts.bob),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+            assert_eq!(
+                marketplace.cancel_fee_recipient(),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn add_and_remove_currency_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert!(marketplace.add_currency(accounts.django).is_ok());
+            assert_eq!(
+                marketplace.add_currency(accounts.django),
+                Err(MarketplaceError::CurrencyAlreadyAllowed)
+            );
+            assert_eq!(marketplace.get_currencies(), vec![accounts.django]);
+
+            assert!(marketplace.remove_currency(accounts.django).is_ok());
+            assert_eq!(
+                marketplace.remove_currency(accounts.django),
+                Err(MarketplaceError::CurrencyNotAllowed)
+            );
+            assert!(marketplace.get_currencies().is_empty());
+        }
+
+        #[ink::test]
+        fn add_currency_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.add_currency(accounts.django),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn list_fails_if_currency_not_allowed() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.list_with_options(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    ListingOptions {
+                        currency: Some(accounts.django),
+                        ..Default::default()
+                    }
+                ),
+                Err(MarketplaceError::CurrencyNotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn buy_fails_if_unlisted_token() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.buy(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn check_price_returns_surplus() {
+            let marketplace = init_contract();
+
+            assert_eq!(marketplace.check_price(100, 100), Ok(0));
+            assert_eq!(marketplace.check_price(150, 100), Ok(50));
+            assert_eq!(
+                marketplace.check_price(99, 100),
+                Err(MarketplaceError::BadBuyValue)
+            );
+        }
+
+        #[ink::test]
+        fn split_price_charges_fees_on_price() {
+            let mut marketplace = init_contract();
+            assert_eq!(
+                marketplace.split_price(contract_address(), Id::U128(1), 1000, None, 0, None),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+
+            register_contract(&mut marketplace);
+            assert_eq!(
+                marketplace.split_price(contract_address(), Id::U128(1), 1000, None, 0, None),
+                Ok((980, 10, None, royalty_receivers(), 10))
+            );
+        }
+
+        #[ink::test]
+        fn split_price_pays_referral_share_of_marketplace_fee() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    10_000,
+                    Some(accounts.bob),
+                    0,
+                    None
+                ),
+                Ok((9800, 100, Some((accounts.bob, 0)), royalty_receivers(), 100))
+            );
+            assert!(marketplace.set_referral_share(2_000).is_ok());
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    10_000,
+                    Some(accounts.bob),
+                    0,
+                    None
+                ),
+                Ok((9800, 80, Some((accounts.bob, 20)), royalty_receivers(), 100))
+            );
+            assert_eq!(marketplace.get_referral_earnings(accounts.bob, None), 0);
+        }
+
+        #[ink::test]
+        fn split_price_applies_fee_discount() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+            assert!(marketplace.set_referral_share(5_000).is_ok());
+
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    10_000,
+                    Some(accounts.bob),
+                    2_500,
+                    None
+                ),
+                Ok((9825, 38, Some((accounts.bob, 37)), royalty_receivers(), 100))
+            );
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    10_000,
+                    None,
+                    10_000,
+                    None
+                ),
+                Ok((9900, 0, None, royalty_receivers(), 100))
+            );
+        }
+
+        #[ink::test]
+        fn set_discount_tiers_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            for tiers in [
+                vec![(5, 5_000), (1, 2_500)],
+                vec![(1, 2_500), (1, 5_000)],
+                vec![(1, 10_001)],
+                (1..12).map(|tokens| (tokens, 100)).collect(),
+            ] {
+                assert_eq!(
+                    marketplace.set_discount_tiers(tiers),
+                    Err(MarketplaceError::InvalidDiscountTiers)
+                );
+            }
+            assert!(marketplace
+                .set_discount_tiers(vec![(1, 2_500), (5, 5_000)])
+                .is_ok());
+            assert_eq!(
+                marketplace.get_discount_tiers(),
+                vec![(1, 2_500), (5, 5_000)]
+            );
+            assert_eq!(marketplace.tier_discount(0), 0);
+            assert_eq!(marketplace.tier_discount(1), 2_500);
+            assert_eq!(marketplace.tier_discount(4), 2_500);
+            assert_eq!(marketplace.tier_discount(5), 5_000);
+            assert_eq!(marketplace.tier_discount(100), 5_000);
+
+            // No discount until membership collection is set.
+            assert_eq!(marketplace.get_fee_discount(accounts.bob), 0);
+            assert!(marketplace
+                .set_membership_collection(Some(contract_address()))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_membership_collection(),
+                Some(contract_address())
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_discount_tiers(vec![]),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+            assert_eq!(
+                marketplace.set_membership_collection(None),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_referral_share_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.set_referral_share(10_001),
+                Err(MarketplaceError::FeeTooHigh)
+            );
+            assert!(marketplace.set_referral_share(10_000).is_ok());
+            assert_eq!(marketplace.get_referral_share(), 10_000);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.set_referral_share(0),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn split_price_prefers_nft_royalty() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    1000,
+                    None,
+                    0,
+                    Some((accounts.bob, 50))
+                ),
+                Ok((940, 10, None, vec![(accounts.bob, 10_000)], 50))
+            );
+            // Royalty reported by NFT contract is capped by the max fee.
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    1000,
+                    None,
+                    0,
+                    Some((accounts.bob, 500))
+                ),
+                Ok((890, 10, None, vec![(accounts.bob, 10_000)], 100))
+            );
+        }
+
+        #[ink::test]
+        fn split_price_keeps_registered_royalty_split() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            // Factory passes only the first royalty receiver to the NFT contract.
+            let royalty_receivers = vec![(accounts.bob, 6_000), (accounts.charlie, 4_000)];
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    royalty_receivers.clone(),
+                    100,
+                    String::from("ipfs")
+                )
+                .is_ok());
+
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    1000,
+                    None,
+                    0,
+                    Some((accounts.bob, 10))
+                ),
+                Ok((980, 10, None, royalty_receivers, 10))
+            );
+        }
+
+        #[ink::test]
+        fn split_price_prefers_token_royalty_override() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+            assert!(marketplace
+                .set_token_royalty(contract_address(), Id::U128(1), Some(200))
+                .is_ok());
+
+            assert_eq!(
+                marketplace.split_price(
+                    contract_address(),
+                    Id::U128(1),
+                    1000,
+                    None,
+                    0,
+                    Some((accounts.bob, 50))
+                ),
+                Ok((970, 10, None, royalty_receivers(), 20))
+            );
+        }
+
+        #[ink::test]
+        fn propose_collection_fee_works() {
+            let mut marketplace = init_contract();
+            marketplace.marketplace.fee_change_delay = 10;
+            assert_eq!(
+                marketplace.propose_collection_fee(contract_address(), Some(0)),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+
+            register_contract(&mut marketplace);
+            assert_eq!(
+                marketplace.propose_collection_fee(contract_address(), Some(1001)),
+                Err(MarketplaceError::FeeTooHigh)
+            );
+            // Fee decrease is applied immediately.
+            assert!(marketplace
+                .propose_collection_fee(contract_address(), Some(0))
+                .is_ok());
+            assert_eq!(marketplace.get_effective_fee(contract_address()), 0);
+            assert_eq!(
+                marketplace.split_price(contract_address(), Id::U128(1), 1000, None, 0, None),
+                Ok((990, 0, None, royalty_receivers(), 10))
+            );
+
+            // Removing the override raises the fee back to the global one, so it waits for the delay.
+            assert!(marketplace
+                .propose_collection_fee(contract_address(), None)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_pending_collection_fee(contract_address()),
+                Some((None, 10))
+            );
+            assert_eq!(
+                marketplace.execute_collection_fee(contract_address()),
+                Err(MarketplaceError::FeeChangeNotReady)
+            );
+            assert_eq!(marketplace.get_effective_fee(contract_address()), 0);
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert!(marketplace
+                .execute_collection_fee(contract_address())
+                .is_ok());
+            assert_eq!(marketplace.get_effective_fee(contract_address()), 100);
+            assert_eq!(
+                marketplace.get_pending_collection_fee(contract_address()),
+                None
+            );
+            assert_eq!(4, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn cancel_collection_fee_works() {
+            let mut marketplace = init_contract();
+            register_contract(&mut marketplace);
+            assert_eq!(
+                marketplace.cancel_collection_fee(contract_address()),
+                Err(MarketplaceError::NoPendingFeeChange)
+            );
+
+            assert!(marketplace
+                .propose_collection_fee(contract_address(), Some(200))
+                .is_ok());
+            assert!(marketplace
+                .cancel_collection_fee(contract_address())
+                .is_ok());
+            assert_eq!(
+                marketplace.get_pending_collection_fee(contract_address()),
+                None
+            );
+            assert_eq!(marketplace.get_effective_fee(contract_address()), 100);
+            assert_eq!(
+                marketplace.execute_collection_fee(contract_address()),
+                Err(MarketplaceError::NoPendingFeeChange)
+            );
+        }
+
+        #[ink::test]
+        fn collection_fee_decrease_cancels_pending_increase() {
+            let mut marketplace = init_contract();
+            register_contract(&mut marketplace);
+            marketplace.marketplace.fee_change_delay = 10;
+
+            assert!(marketplace
+                .propose_collection_fee(contract_address(), Some(200))
+                .is_ok());
+            assert!(marketplace
+                .propose_collection_fee(contract_address(), Some(50))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_pending_collection_fee(contract_address()),
+                None
+            );
+
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.execute_collection_fee(contract_address()),
+                Err(MarketplaceError::NoPendingFeeChange)
+            );
+            assert_eq!(marketplace.get_effective_fee(contract_address()), 50);
+        }
+
+        #[ink::test]
+        fn propose_collection_fee_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.propose_collection_fee(contract_address(), Some(0)),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn set_token_royalty_works() {
+            let mut marketplace = init_contract();
+            assert_eq!(
+                marketplace.set_token_royalty(contract_address(), Id::U128(1), Some(500)),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+            assert_eq!(
+                marketplace.get_effective_royalty(contract_address(), Id::U128(1)),
+                None
+            );
+
+            register_contract(&mut marketplace);
+            assert_eq!(
+                marketplace.set_token_royalty(contract_address(), Id::U128(1), Some(1001)),
+                Err(MarketplaceError::FeeTooHigh)
+            );
+            assert!(marketplace
+                .set_token_royalty(contract_address(), Id::U128(1), Some(500))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_effective_royalty(contract_address(), Id::U128(1)),
+                Some(500)
+            );
+            assert_eq!(
+                marketplace.get_effective_royalty(contract_address(), Id::U128(2)),
+                Some(100)
+            );
+            assert_eq!(
+                marketplace.split_price(contract_address(), Id::U128(1), 1000, None, 0, None),
+                Ok((940, 10, None, royalty_receivers(), 50))
+            );
+
+            assert!(marketplace
+                .set_token_royalty(contract_address(), Id::U128(1), None)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_effective_royalty(contract_address(), Id::U128(1)),
+                Some(100)
+            );
+            assert_eq!(3, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn withdraw_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            assert!(marketplace
+                .transfer_funds(None, accounts.charlie, accounts.bob, 100)
+                .is_ok());
+            assert!(marketplace
+                .transfer_funds(None, accounts.charlie, accounts.bob, 50)
+                .is_ok());
+            assert_eq!(marketplace.pending_balance(accounts.bob), 150);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.withdraw(),
+                Err(MarketplaceError::NothingToWithdraw)
+            );
+
+            set_sender(accounts.bob);
+            let bob_balance = test::get_account_balance::<Environment>(accounts.bob).unwrap();
+            assert_eq!(marketplace.withdraw(), Ok(150));
+            assert_eq!(marketplace.pending_balance(accounts.bob), 0);
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.bob).unwrap(),
+                bob_balance + 150
+            );
+            assert_eq!(
+                marketplace.withdraw(),
+                Err(MarketplaceError::NothingToWithdraw)
+            );
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn withdraw_to_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.add_pending_balance(accounts.bob, 100);
+
+            set_sender(accounts.bob);
+            let django_balance = test::get_account_balance::<Environment>(accounts.django).unwrap();
+            assert_eq!(marketplace.withdraw_to(accounts.django), Ok(100));
+            assert_eq!(marketplace.pending_balance(accounts.bob), 0);
+            assert_eq!(
+                test::get_account_balance::<Environment>(accounts.django).unwrap(),
+                django_balance + 100
+            );
+        }
+
+        #[ink::test]
+        fn list_batch_reports_item_results() {
+            let mut marketplace = init_contract();
+            let items = vec![
+                (contract_address(), Id::U128(1), 100),
+                (contract_address(), Id::U128(2), 100),
+            ];
+
+            assert_eq!(
+                marketplace.list_batch(items.clone(), true),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+            assert_eq!(
+                marketplace.list_batch(items, false),
+                Ok(vec![
+                    Err(MarketplaceError::NotRegisteredContract),
+                    Err(MarketplaceError::NotRegisteredContract)
+                ])
+            );
+            assert_eq!(
+                marketplace.unlist_batch(vec![(contract_address(), Id::U128(1))], false),
+                Ok(vec![Err(MarketplaceError::ItemNotListedForSale)])
+            );
+        }
+
+        #[ink::test]
+        fn buy_batch_credits_unspent_value() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let items = vec![(contract_address(), Id::U128(1))];
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.buy_batch(items.clone(), true),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+            assert_eq!(
+                marketplace.buy_batch(items, false),
+                Ok(vec![Err(MarketplaceError::ItemNotListedForSale)])
+            );
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 100);
+        }
+
+        #[ink::test]
+        fn delist_token_removes_sold_item() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_item(&mut marketplace, accounts.charlie, 100);
+            let mut item = marketplace
+                .get_listing(contract_address(), Id::U128(1))
+                .unwrap();
+            item.escrowed = true;
+            marketplace
+                .marketplace
+                .items
+                .insert(&(contract_address(), Id::U128(1)), &item);
+
+            // Batch and single buys delist the token once it is sold.
+            marketplace.delist_token(contract_address(), Id::U128(1));
+            assert!(marketplace
+                .get_listing(contract_address(), Id::U128(1))
+                .is_none());
+            assert_eq!(marketplace.get_price(contract_address(), Id::U128(1)), None);
+        }
+
+        #[ink::test]
+        fn delist_token_removes_bundle() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_bundle(&mut marketplace, accounts.charlie, vec![1, 1]);
+
+            // Token sold through an offer or a swap invalidates its bundle.
+            marketplace.delist_token(contract_address(), Id::U128(1));
+            assert_eq!(marketplace.get_bundle(1), None);
+            assert_eq!(
+                marketplace.get_token_bundle(contract_address(), Id::U128(2)),
+                None
+            );
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn register_contract_works() {
+            let mut marketplace = init_contract();
+            let ipfs = String::from("ipfs");
+
+            assert!(marketplace
+                .register(contract_address(), royalty_receivers(), 999, ipfs.clone())
+                .is_ok());
+            let contract = marketplace
+                .get_registered_collection(contract_address())
+                .unwrap();
+            assert_eq!(contract.royalty_receivers, royalty_receivers());
+            assert_eq!(contract.royalty, 999);
+            assert_eq!(contract.marketplace_ipfs, ipfs);
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn register_fails_if_fee_too_high() {
+            let mut marketplace = init_contract();
+            let ipfs = String::from("ipfs");
+
+            assert_eq!(
+                marketplace.register(contract_address(), royalty_receivers(), 1001, ipfs.clone()),
+                Err(MarketplaceError::FeeTooHigh)
+            );
+            assert!(marketplace
+                .register(contract_address(), royalty_receivers(), 999, ipfs)
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn register_fails_if_contract_already_registered() {
+            let mut marketplace = init_contract();
+            let ipfs = String::from("ipfs");
+
+            assert!(marketplace
+                .register(contract_address(), royalty_receivers(), 999, ipfs.clone())
+                .is_ok());
+            assert_eq!(
+                marketplace.register(contract_address(), royalty_receivers(), 999, ipfs),
+                Err(MarketplaceError::ContractAlreadyRegistered)
+            );
+        }
+
+        #[ink::test]
+        fn register_fails_if_invalid_royalty_split() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let ipfs = String::from("ipfs");
+
+            for receivers in [
+                vec![],
+                vec![(accounts.bob, 5_000), (accounts.charlie, 4_999)],
+                vec![(accounts.bob, 5_000), (accounts.charlie, 5_001)],
+                vec![(accounts.bob, 1_000); 11],
+            ] {
+                assert_eq!(
+                    marketplace.register(contract_address(), receivers, 100, ipfs.clone()),
+                    Err(MarketplaceError::InvalidRoyaltySplit)
+                );
+            }
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    vec![(accounts.bob, 1_000); 10],
+                    100,
+                    ipfs
+                )
+                .is_ok());
+        }
+
+        #[ink::test]
+        fn royalty_shares_gives_remainder_to_first_receiver() {
+            let marketplace = init_contract();
+            let accounts = default_accounts();
+            let receivers = vec![
+                (accounts.bob, 3_334),
+                (accounts.charlie, 3_333),
+                (accounts.django, 3_333),
+            ];
+
+            assert_eq!(
+                marketplace.royalty_shares(&receivers, 100),
+                vec![
+                    (accounts.bob, 34),
+                    (accounts.charlie, 33),
+                    (accounts.django, 33)
+                ]
+            );
+            assert_eq!(
+                marketplace.royalty_shares(&receivers, 10),
+                vec![
+                    (accounts.bob, 4),
+                    (accounts.charlie, 3),
+                    (accounts.django, 3)
+                ]
+            );
+            assert_eq!(
+                marketplace.royalty_shares(&receivers, 1),
+                vec![
+                    (accounts.bob, 1),
+                    (accounts.charlie, 0),
+                    (accounts.django, 0)
+                ]
+            );
+        }
+
+        #[ink::test]
+        fn set_nft_contract_hash_works() {
+            let mut marketplace = init_contract();
+            let hash = Hash::try_from([1; 32]).unwrap();
+            let hash2 = Hash::try_from([2; 32]).unwrap();
+
+            assert!(marketplace
+                .set_nft_contract_hash(NftContractType::Rmrk, hash)
+                .is_ok());
+            assert_eq!(marketplace.nft_contract_hash(NftContractType::Rmrk), hash);
+
+            // Check also if owner is able to update hash.
+            assert!(marketplace
+                .set_nft_contract_hash(NftContractType::Rmrk, hash2)
+                .is_ok());
+            assert_eq!(marketplace.nft_contract_hash(NftContractType::Rmrk), hash2);
+        }
+
+        #[ink::test]
+        fn set_nft_contract_fails_if_not_owner() {
+            let mut marketplace = init_contract();
+            let hash = Hash::try_from([1; 32]).unwrap();
+            let accounts = default_accounts();
+            set_sender(accounts.bob);
+
+            assert_eq!(
+                marketplace.set_nft_contract_hash(NftContractType::Rmrk, hash),
+                Err(MarketplaceError::OwnableError(
+                    OwnableError::CallerIsNotOwner
+                ))
+            );
+        }
+
+        #[ink::test]
+        fn factory_fails_if_no_hash() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            assert_eq!(
+                marketplace.factory(
+                    String::from("ipfs"),
+                    vec![(accounts.alice, 10_000)],
+                    100,
+                    String::from("name"),
+                    String::from("symbol"),
+                    String::from("base_uri"),
+                    0,
+                    0,
+                    NftContractType::Psp34
+                ),
+                Err(MarketplaceError::NftContractHashNotSet)
+            );
+        }
+
+        #[ink::test]
+        fn list_fails_if_invalid_period() {
+            let mut marketplace = init_contract();
+            test::advance_block::<Environment>();
+
+            assert_eq!(
+                marketplace.list_with_options(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    ListingOptions {
+                        expires_at: Some(6),
+                        ..Default::default()
+                    }
+                ),
+                Err(MarketplaceError::InvalidListingPeriod)
+            );
+            assert_eq!(
+                marketplace.list_with_options(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    ListingOptions {
+                        starts_at: Some(12),
+                        expires_at: Some(12),
+                        ..Default::default()
+                    }
+                ),
+                Err(MarketplaceError::InvalidListingPeriod)
+            );
+            assert_eq!(
+                marketplace.list_with_options(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    ListingOptions {
+                        starts_at: Some(12),
+                        expires_at: Some(18),
+                        ..Default::default()
+                    }
+                ),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+        }
+
+        #[ink::test]
+        fn is_listing_fillable_fails_if_not_active() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+
+            assert!(!marketplace.is_listing_fillable(contract_address(), Id::U128(1)));
+
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner: accounts.bob,
+                    price: 100,
+                    dutch_auction: None,
+                    starts_at: Some(6),
+                    expires_at: None,
+                    currency: None,
+                    escrowed: false,
+                    reserved_for: None,
+                },
+            );
+            assert!(!marketplace.is_listing_fillable(contract_address(), Id::U128(1)));
+        }
+
+        #[ink::test]
+        fn prune_listing_fails_if_not_listed() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.prune_listing(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn unlist_escrowed_fails_if_not_seller() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner: accounts.bob,
+                    price: 100,
+                    dutch_auction: None,
+                    starts_at: None,
+                    expires_at: None,
+                    currency: None,
+                    escrowed: true,
+                    reserved_for: None,
+                },
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.unlist(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn before_received_accepts_only_escrow_transfers() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+
+            // Marketplace contract is deployed at alice's address in the off-chain environment.
+            assert!(marketplace
+                .before_received(accounts.alice, accounts.bob, Id::U128(1), Vec::new())
+                .is_ok());
+            assert!(marketplace
+                .before_received(accounts.bob, accounts.bob, Id::U128(1), Vec::new())
+                .is_err());
+        }
+
+        #[ink::test]
+        fn buy_fails_if_listing_reserved() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner: accounts.bob,
+                    price: 100,
+                    dutch_auction: None,
+                    starts_at: None,
+                    expires_at: None,
+                    currency: None,
+                    escrowed: false,
+                    reserved_for: Some(accounts.django),
+                },
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.buy(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::ListingReserved)
+            );
+        }
+
+        #[ink::test]
+        fn buy_fails_if_listing_not_active() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner: accounts.bob,
+                    price: 100,
+                    dutch_auction: None,
+                    starts_at: Some(6),
+                    expires_at: Some(12),
+                    currency: None,
+                    escrowed: false,
+                    reserved_for: None,
+                },
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(marketplace.get_price(contract_address(), Id::U128(1)), None);
+            assert_eq!(
+                marketplace.buy(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::ListingNotStarted)
+            );
+
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(100)
+            );
+
+            test::advance_block::<Environment>();
+            assert_eq!(marketplace.get_price(contract_address(), Id::U128(1)), None);
+            assert_eq!(
+                marketplace.buy(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::ListingExpired)
+            );
+            assert_eq!(
+                marketplace
+                    .get_listing(contract_address(), Id::U128(1))
+                    .unwrap()
+                    .expires_at,
+                Some(12)
+            );
+        }
+
+        #[ink::test]
+        fn update_price_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_item(&mut marketplace, accounts.bob, 100);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.update_price(contract_address(), Id::U128(1), 50),
+                Err(MarketplaceError::NotOwner)
+            );
+            assert_eq!(
+                marketplace.update_price(contract_address(), Id::U128(2), 50),
+                Err(MarketplaceError::ItemNotListedForSale)
+            );
+
+            set_sender(accounts.bob);
+            assert!(marketplace
+                .update_price(contract_address(), Id::U128(1), 150)
+                .is_ok());
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(150)
+            );
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn list_dutch_fails_if_invalid_price_decay() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.list_dutch(contract_address(), Id::U128(1), 100, 101, 0, 12, None),
+                Err(MarketplaceError::InvalidDutchAuction)
+            );
+            assert_eq!(
+                marketplace.list_dutch(contract_address(), Id::U128(1), 100, 50, 12, 12, None),
+                Err(MarketplaceError::InvalidDutchAuction)
+            );
+        }
+
+        #[ink::test]
+        fn get_price_declines_for_dutch_auction() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner: accounts.bob,
+                    price: 300,
+                    dutch_auction: Some(DutchAuction {
+                        end_price: 100,
+                        start_time: 6,
+                        end_time: 18,
+                    }),
+                    starts_at: None,
+                    expires_at: None,
+                    currency: None,
+                    escrowed: false,
+                    reserved_for: None,
+                },
+            );
+
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(300)
+            );
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(300)
+            );
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(200)
+            );
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(100)
+            );
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.get_price(contract_address(), Id::U128(1)),
+                Some(100)
+            );
+        }
+
+        #[ink::test]
+        fn create_auction_fails_if_invalid_period() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.create_auction(contract_address(), Id::U128(1), 100, 10, 10),
+                Err(MarketplaceError::InvalidAuctionPeriod)
+            );
+            assert_eq!(
+                marketplace.create_auction(contract_address(), Id::U128(1), 100, 0, 10),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+        }
+
+        #[ink::test]
+        fn place_bid_fails_if_no_auction() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.place_bid(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::AuctionNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn place_bid_refunds_previous_bidder() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_auction(&mut marketplace, accounts.bob);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(99);
+            assert_eq!(
+                marketplace.place_bid(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::BidTooLow)
+            );
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace
+                .place_bid(contract_address(), Id::U128(1), None)
+                .is_ok());
+
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.place_bid(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::BidTooLow)
+            );
+            test::set_value_transferred::<Environment>(150);
+            assert_eq!(
+                marketplace.place_bid(contract_address(), Id::U128(1), Some(accounts.bob)),
+                Err(MarketplaceError::InvalidReferrer)
+            );
+            assert_eq!(
+                marketplace.place_bid(contract_address(), Id::U128(1), Some(accounts.django)),
+                Err(MarketplaceError::InvalidReferrer)
+            );
+            assert!(marketplace
+                .place_bid(contract_address(), Id::U128(1), Some(accounts.eve))
+                .is_ok());
+
+            let auction = marketplace
+                .get_auction(contract_address(), Id::U128(1))
+                .unwrap();
+            assert_eq!(auction.highest_bidder, Some(accounts.django));
+            assert_eq!(auction.highest_bid, 150);
+            assert_eq!(auction.referrer, Some(accounts.eve));
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 100);
+            assert_eq!(2, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn place_bid_fails_if_auction_ended() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_auction(&mut marketplace, accounts.bob);
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.place_bid(contract_address(), Id::U128(1), None),
+                Err(MarketplaceError::AuctionEnded)
+            );
+        }
+
+        #[ink::test]
+        fn settle_auction_without_bids_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_auction(&mut marketplace, accounts.bob);
+
+            assert_eq!(
+                marketplace.settle_auction(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::AuctionNotEnded)
+            );
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert!(marketplace
+                .settle_auction(contract_address(), Id::U128(1))
+                .is_ok());
+            assert_eq!(
+                marketplace.get_auction(contract_address(), Id::U128(1)),
+                None
+            );
+        }
+
+        #[ink::test]
+        fn cancel_auction_fails_if_auction_has_bids() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_auction(&mut marketplace, accounts.bob);
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.cancel_auction(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::NotOwner)
+            );
+            test::set_value_transferred::<Environment>(100);
+            assert!(marketplace
+                .place_bid(contract_address(), Id::U128(1), None)
+                .is_ok());
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.cancel_auction(contract_address(), Id::U128(1)),
+                Err(MarketplaceError::AuctionHasBids)
+            );
+        }
+
+        #[ink::test]
+        fn create_sealed_auction_fails_if_invalid_period() {
+            let mut marketplace = init_contract();
+            test::advance_block::<Environment>();
+
+            assert_eq!(
+                marketplace.create_sealed_auction(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    6,
+                    12,
+                    false
+                ),
+                Err(MarketplaceError::InvalidAuctionPeriod)
+            );
+            assert_eq!(
+                marketplace.create_sealed_auction(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    12,
+                    12,
+                    false
+                ),
+                Err(MarketplaceError::InvalidAuctionPeriod)
+            );
+            assert_eq!(
+                marketplace.create_sealed_auction(
+                    contract_address(),
+                    Id::U128(1),
+                    100,
+                    12,
+                    18,
+                    false
+                ),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+        }
+
+        #[ink::test]
+        fn commit_and_reveal_sealed_bids_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_sealed_auction(&mut marketplace, accounts.bob);
+            let salt = Hash::from([0x7; 32]);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(99);
+            assert_eq!(
+                marketplace.commit_sealed_bid(
+                    1,
+                    sealed_bid_commitment(accounts.charlie, 120, salt),
+                    None
+                ),
+                Err(MarketplaceError::BidTooLow)
+            );
+            test::set_value_transferred::<Environment>(150);
+            assert!(marketplace
+                .commit_sealed_bid(1, sealed_bid_commitment(accounts.charlie, 120, salt), None)
+                .is_ok());
+            assert_eq!(
+                marketplace.commit_sealed_bid(
+                    1,
+                    sealed_bid_commitment(accounts.charlie, 120, salt),
+                    None
+                ),
+                Err(MarketplaceError::BidAlreadyCommitted)
+            );
+
+            set_sender(accounts.django);
+            test::set_value_transferred::<Environment>(200);
+            assert!(marketplace
+                .commit_sealed_bid(1, sealed_bid_commitment(accounts.django, 130, salt), None)
+                .is_ok());
+            assert_eq!(
+                marketplace.reveal_sealed_bid(1, 130, salt),
+                Err(MarketplaceError::RevealPhaseNotActive)
+            );
+
+            // Reveal phase starts after two blocks.
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            test::set_value_transferred::<Environment>(0);
+            assert_eq!(
+                marketplace.commit_sealed_bid(
+                    1,
+                    sealed_bid_commitment(accounts.django, 130, salt),
+                    None
+                ),
+                Err(MarketplaceError::CommitPhaseEnded)
+            );
+            assert_eq!(
+                marketplace.reveal_sealed_bid(1, 131, salt),
+                Err(MarketplaceError::InvalidReveal)
+            );
+            assert!(marketplace.reveal_sealed_bid(1, 130, salt).is_ok());
+
+            set_sender(accounts.charlie);
+            assert!(marketplace.reveal_sealed_bid(1, 120, salt).is_ok());
+
+            let auction = marketplace.get_sealed_auction(1).unwrap();
+            assert_eq!(auction.bids, 2);
+            assert_eq!(auction.highest_bidder, Some(accounts.django));
+            assert_eq!(auction.highest_bid, 130);
+            assert_eq!(auction.second_bid, 120);
+            assert_eq!(4, ink::env::test::recorded_events().count());
+        }
 
+        #[ink::test]
+        fn withdraw_sealed_bid_works() {
+            let mut marketplace = init_contract();
             let accounts = default_accounts();
-            set_sender(accounts.bob);
+            insert_sealed_auction(&mut marketplace, accounts.bob);
+            let salt = Hash::from([0x7; 32]);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(150);
+            assert!(marketplace
+                .commit_sealed_bid(1, sealed_bid_commitment(accounts.charlie, 120, salt), None)
+                .is_ok());
+            test::set_value_transferred::<Environment>(0);
             assert_eq!(
-                marketplace.set_marketplace_fee(120),
-                Err(MarketplaceError::OwnableError(
-                    OwnableError::CallerIsNotOwner
-                ))
+                marketplace.withdraw_sealed_bid(1),
+                Err(MarketplaceError::AuctionNotEnded)
+            );
+
+            // Reveal phase ends after four blocks, the bid has not been revealed.
+            for _ in 0..4 {
+                test::advance_block::<Environment>();
+            }
+            assert!(marketplace.settle_sealed_auction(1).is_ok());
+            assert_eq!(marketplace.get_sealed_auction(1), None);
+            assert!(marketplace.withdraw_sealed_bid(1).is_ok());
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 150);
+            assert_eq!(
+                marketplace.withdraw_sealed_bid(1),
+                Err(MarketplaceError::BidNotFound)
             );
         }
 
         #[ink::test]
-        fn set_marketplace_fee_fails_if_fee_too_high() {
+        fn make_offer_fails_if_not_registered_contract() {
             let mut marketplace = init_contract();
 
+            test::set_value_transferred::<Environment>(100);
             assert_eq!(
-                marketplace.set_marketplace_fee(1001),
-                Err(MarketplaceError::FeeTooHigh)
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Err(MarketplaceError::NotRegisteredContract)
             );
-            assert!(marketplace.set_marketplace_fee(1000).is_ok());
         }
 
         #[ink::test]
-        fn set_fee_recipient_works() {
+        fn make_and_cancel_offer_works() {
             let mut marketplace = init_contract();
             let accounts = default_accounts();
+            register_contract(&mut marketplace);
 
-            assert!(marketplace.set_fee_recipient(accounts.bob).is_ok());
-            assert_eq!(marketplace.get_fee_recipient(), accounts.bob);
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(0);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Err(MarketplaceError::BadBuyValue)
+            );
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 0),
+                Err(MarketplaceError::OfferExpired)
+            );
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Ok(1)
+            );
+            set_sender(accounts.django);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Ok(2)
+            );
+            assert_eq!(
+                marketplace.get_offers(contract_address(), Id::U128(1)),
+                vec![1, 2]
+            );
+            assert_eq!(marketplace.get_offer(2).unwrap().buyer, accounts.django);
+
+            assert_eq!(
+                marketplace.cancel_offer(1),
+                Err(MarketplaceError::NotOfferOwner)
+            );
+            set_sender(accounts.charlie);
+            assert!(marketplace.cancel_offer(1).is_ok());
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 100);
+            assert_eq!(marketplace.get_offer(1), None);
+            assert_eq!(
+                marketplace.get_offers(contract_address(), Id::U128(1)),
+                vec![2]
+            );
+            assert_eq!(
+                marketplace.accept_offer(1),
+                Err(MarketplaceError::OfferNotFound)
+            );
         }
 
         #[ink::test]
-        fn set_fee_recipient_fails_if_not_owner() {
+        fn make_offer_fails_if_below_min_offer_price() {
             let mut marketplace = init_contract();
             let accounts = default_accounts();
-            set_sender(accounts.bob);
+            register_contract(&mut marketplace);
 
+            set_sender(accounts.charlie);
             assert_eq!(
-                marketplace.set_fee_recipient(accounts.bob),
+                marketplace.set_min_offer_price(50),
                 Err(MarketplaceError::OwnableError(
                     OwnableError::CallerIsNotOwner
                 ))
             );
+            set_sender(accounts.alice);
+            assert!(marketplace.set_min_offer_price(50).is_ok());
+            assert_eq!(marketplace.get_min_offer_price(), 50);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(49);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Err(MarketplaceError::OfferTooLow)
+            );
+            test::set_value_transferred::<Environment>(50);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Ok(1)
+            );
         }
 
         #[ink::test]
-        fn buy_fails_if_unlisted_token() {
+        fn make_offer_replaces_lowest_offer_if_limit_reached() {
             let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
 
+            set_sender(accounts.charlie);
+            for i in 0..MAX_OFFERS_PER_TOKEN {
+                test::set_value_transferred::<Environment>(200 - i as u128);
+                assert!(marketplace
+                    .make_offer(contract_address(), Id::U128(1), 12)
+                    .is_ok());
+            }
+            let lowest_offer = MAX_OFFERS_PER_TOKEN as u64;
+
+            set_sender(accounts.django);
+            test::set_value_transferred::<Environment>(200 - lowest_offer as u128 + 1);
             assert_eq!(
-                marketplace.buy(contract_address(), Id::U128(1)),
-                Err(MarketplaceError::ItemNotListedForSale)
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Err(MarketplaceError::OfferTooLow)
+            );
+            test::set_value_transferred::<Environment>(200);
+            assert_eq!(
+                marketplace.make_offer(contract_address(), Id::U128(1), 12),
+                Ok(lowest_offer + 1)
+            );
+            assert_eq!(marketplace.get_offer(lowest_offer), None);
+            assert_eq!(
+                marketplace.pending_balance(accounts.charlie),
+                200 - lowest_offer as u128 + 1
+            );
+            assert_eq!(
+                marketplace
+                    .get_offers(contract_address(), Id::U128(1))
+                    .len(),
+                MAX_OFFERS_PER_TOKEN
             );
         }
 
         #[ink::test]
-        fn register_contract_works() {
+        fn accept_trait_offer_fails_if_not_trait_offer() {
             let mut marketplace = init_contract();
-            let ipfs = String::from("ipfs");
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
 
-            assert!(marketplace
-                .register(contract_address(), fee_recipient(), 999, ipfs.clone())
-                .is_ok());
-            let contract = marketplace
-                .get_registered_collection(contract_address())
-                .unwrap();
-            assert_eq!(contract.royalty_receiver, fee_recipient());
-            assert_eq!(contract.royalty, 999);
-            assert_eq!(contract.marketplace_ipfs, ipfs);
-            assert_eq!(1, ink::env::test::recorded_events().count());
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.make_collection_offer(contract_address(), 100, 1, 12),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.make_trait_offer(
+                    contract_address(),
+                    String::from("background"),
+                    String::from("gold"),
+                    100,
+                    1,
+                    12
+                ),
+                Ok(2)
+            );
+            assert_eq!(
+                marketplace.get_collection_offer(2).unwrap().attribute,
+                Some((String::from("background"), String::from("gold")))
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.accept_trait_offer(1, Id::U128(1)),
+                Err(MarketplaceError::NotTraitOffer)
+            );
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            assert_eq!(
+                marketplace.accept_trait_offer(2, Id::U128(1)),
+                Err(MarketplaceError::OfferExpired)
+            );
         }
 
         #[ink::test]
-        fn register_fails_if_fee_too_high() {
+        fn make_and_cancel_collection_offer_works() {
             let mut marketplace = init_contract();
-            let ipfs = String::from("ipfs");
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
 
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(299);
             assert_eq!(
-                marketplace.register(contract_address(), fee_recipient(), 1001, ipfs.clone()),
-                Err(MarketplaceError::FeeTooHigh)
+                marketplace.make_collection_offer(contract_address(), 100, 3, 12),
+                Err(MarketplaceError::BadBuyValue)
+            );
+            test::set_value_transferred::<Environment>(300);
+            assert_eq!(
+                marketplace.make_collection_offer(contract_address(), 100, 3, 12),
+                Ok(1)
+            );
+            assert_eq!(
+                marketplace.get_collection_offers(contract_address()),
+                vec![1]
             );
+            assert_eq!(marketplace.get_collection_offer(1).unwrap().quantity, 3);
+            assert!(marketplace.cancel_collection_offer(1).is_ok());
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 300);
             assert!(marketplace
-                .register(contract_address(), fee_recipient(), 999, ipfs)
-                .is_ok());
+                .get_collection_offers(contract_address())
+                .is_empty());
         }
 
         #[ink::test]
-        fn register_fails_if_contract_already_registered() {
+        fn accept_collection_offer_fails_if_below_min_price() {
             let mut marketplace = init_contract();
-            let ipfs = String::from("ipfs");
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
 
-            assert!(marketplace
-                .register(contract_address(), fee_recipient(), 999, ipfs.clone())
-                .is_ok());
+            set_sender(accounts.bob);
             assert_eq!(
-                marketplace.register(contract_address(), fee_recipient(), 999, ipfs),
-                Err(MarketplaceError::ContractAlreadyRegistered)
+                marketplace.accept_collection_offer(contract_address(), Id::U128(1), 100),
+                Err(MarketplaceError::NoCollectionOffer)
+            );
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.make_collection_offer(contract_address(), 100, 1, 12),
+                Ok(1)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.accept_collection_offer(contract_address(), Id::U128(1), 101),
+                Err(MarketplaceError::OfferBelowMinPrice)
             );
         }
 
         #[ink::test]
-        fn set_nft_contract_hash_works() {
+        fn make_collection_offer_prunes_expired_and_replaces_lowest_offer() {
             let mut marketplace = init_contract();
-            let hash = Hash::try_from([1; 32]).unwrap();
-            let hash2 = Hash::try_from([2; 32]).unwrap();
+            let accounts = default_accounts();
+            register_contract(&mut marketplace);
+
+            set_sender(accounts.charlie);
+            test::set_value_transferred::<Environment>(100);
+            assert_eq!(
+                marketplace.make_collection_offer(contract_address(), 100, 1, 1),
+                Ok(1)
+            );
+            for _ in 1..MAX_COLLECTION_OFFERS {
+                assert!(marketplace
+                    .make_collection_offer(contract_address(), 100, 1, 12)
+                    .is_ok());
+            }
 
+            // Expired offer is pruned to make room for a new offer.
+            test::advance_block::<Environment>();
+            set_sender(accounts.django);
             assert!(marketplace
-                .set_nft_contract_hash(NftContractType::Rmrk, hash)
+                .make_collection_offer(contract_address(), 100, 1, 12)
                 .is_ok());
-            assert_eq!(marketplace.nft_contract_hash(NftContractType::Rmrk), hash);
+            assert_eq!(marketplace.get_collection_offer(1), None);
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 100);
 
-            // Check also if owner is able to update hash.
+            assert_eq!(
+                marketplace.make_collection_offer(contract_address(), 100, 1, 12),
+                Err(MarketplaceError::OfferTooLow)
+            );
+            test::set_value_transferred::<Environment>(101);
             assert!(marketplace
-                .set_nft_contract_hash(NftContractType::Rmrk, hash2)
+                .make_collection_offer(contract_address(), 101, 1, 12)
                 .is_ok());
-            assert_eq!(marketplace.nft_contract_hash(NftContractType::Rmrk), hash2);
+            assert_eq!(marketplace.get_collection_offer(2), None);
+            assert_eq!(marketplace.pending_balance(accounts.charlie), 200);
+            assert_eq!(
+                marketplace.get_collection_offers(contract_address()).len(),
+                MAX_COLLECTION_OFFERS
+            );
         }
 
         #[ink::test]
-        fn set_nft_contract_fails_if_not_owner() {
+        fn list_bundle_fails_if_invalid_size() {
+            let mut marketplace = init_contract();
+
+            assert_eq!(
+                marketplace.list_bundle(vec![(contract_address(), Id::U128(1), 0)], 100),
+                Err(MarketplaceError::InvalidBundleSize)
+            );
+            assert_eq!(
+                marketplace.list_bundle(
+                    vec![
+                        (contract_address(), Id::U128(1), 0),
+                        (contract_address(), Id::U128(1), 0)
+                    ],
+                    100
+                ),
+                Err(MarketplaceError::InvalidBundleSize)
+            );
+            assert_eq!(
+                marketplace.list_bundle(
+                    vec![
+                        (contract_address(), Id::U128(1), 0),
+                        (contract_address(), Id::U128(2), 0)
+                    ],
+                    100
+                ),
+                Err(MarketplaceError::NotRegisteredContract)
+            );
+        }
+
+        #[ink::test]
+        fn list_bundle_fails_if_token_listed() {
             let mut marketplace = init_contract();
-            let hash = Hash::try_from([1; 32]).unwrap();
             let accounts = default_accounts();
+            insert_item(&mut marketplace, accounts.bob, 100);
+
             set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.list_bundle(
+                    vec![
+                        (contract_address(), Id::U128(1), 0),
+                        (contract_address(), Id::U128(2), 0)
+                    ],
+                    100
+                ),
+                Err(MarketplaceError::ItemAlreadyListedForSale)
+            );
+        }
+
+        #[ink::test]
+        fn list_fails_if_token_in_bundle() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_bundle(&mut marketplace, accounts.bob, vec![0, 0]);
 
+            set_sender(accounts.bob);
             assert_eq!(
-                marketplace.set_nft_contract_hash(NftContractType::Rmrk, hash),
-                Err(MarketplaceError::OwnableError(
-                    OwnableError::CallerIsNotOwner
-                ))
+                marketplace.list(contract_address(), Id::U128(1), 100),
+                Err(MarketplaceError::TokenAlreadyInBundle)
+            );
+            assert_eq!(
+                marketplace.list_dutch(contract_address(), Id::U128(2), 100, 50, 0, 12, None),
+                Err(MarketplaceError::TokenAlreadyInBundle)
             );
         }
 
         #[ink::test]
-        fn factory_fails_if_no_hash() {
+        fn unlist_bundle_works() {
             let mut marketplace = init_contract();
             let accounts = default_accounts();
+            insert_bundle(&mut marketplace, accounts.bob, vec![0, 0]);
 
+            set_sender(accounts.bob);
             assert_eq!(
-                marketplace.factory(
-                    String::from("ipfs"),
-                    accounts.alice,
-                    100,
-                    String::from("name"),
-                    String::from("symbol"),
-                    String::from("base_uri"),
-                    0,
-                    0,
-                    NftContractType::Psp34
+                marketplace.buy_bundle(1),
+                Err(MarketplaceError::AlreadyOwner)
+            );
+            assert!(marketplace.unlist_bundle(1).is_ok());
+            assert_eq!(marketplace.get_bundle(1), None);
+            assert_eq!(
+                marketplace.get_token_bundle(contract_address(), Id::U128(1)),
+                None
+            );
+            assert_eq!(
+                marketplace.unlist_bundle(1),
+                Err(MarketplaceError::BundleNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn bundle_shares_works() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_bundle(&mut marketplace, accounts.bob, vec![0, 0, 0]);
+            let bundle = marketplace.get_bundle(1).unwrap();
+            assert_eq!(
+                marketplace.bundle_shares(&bundle, 100),
+                Ok(vec![34, 33, 33])
+            );
+
+            insert_bundle(&mut marketplace, accounts.bob, vec![1, 3]);
+            let bundle = marketplace.get_bundle(1).unwrap();
+            assert_eq!(marketplace.bundle_shares(&bundle, 100), Ok(vec![25, 75]));
+            assert_eq!(
+                marketplace.bundle_shares(&bundle, Balance::MAX),
+                Err(MarketplaceError::BadBuyValue)
+            );
+        }
+
+        #[ink::test]
+        fn make_swap_fails_if_invalid() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            let offered = vec![(contract_address(), Id::U128(1))];
+
+            assert_eq!(
+                marketplace.make_swap(
+                    offered.clone(),
+                    SwapTarget::Collection(contract_address()),
+                    0
                 ),
-                Err(MarketplaceError::NftContractHashNotSet)
+                Err(MarketplaceError::SwapExpired)
+            );
+            assert_eq!(
+                marketplace.make_swap(Vec::new(), SwapTarget::Collection(contract_address()), 12),
+                Err(MarketplaceError::InvalidSwap)
+            );
+            assert_eq!(
+                marketplace.make_swap(
+                    vec![
+                        (contract_address(), Id::U128(1)),
+                        (contract_address(), Id::U128(1))
+                    ],
+                    SwapTarget::Collection(contract_address()),
+                    12
+                ),
+                Err(MarketplaceError::InvalidSwap)
+            );
+            assert_eq!(
+                marketplace.make_swap(offered.clone(), SwapTarget::Tokens(Vec::new()), 12),
+                Err(MarketplaceError::InvalidSwap)
+            );
+            assert_eq!(
+                marketplace.make_swap(offered, SwapTarget::Collection(accounts.django), 12),
+                Err(MarketplaceError::NotRegisteredContract)
             );
         }
 
+        #[ink::test]
+        fn cancel_swap_credits_top_up() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_swap(&mut marketplace, accounts.bob);
+
+            set_sender(accounts.charlie);
+            assert_eq!(marketplace.cancel_swap(1), Err(MarketplaceError::NotOwner));
+
+            set_sender(accounts.bob);
+            assert!(marketplace.cancel_swap(1).is_ok());
+            assert_eq!(marketplace.get_swap(1), None);
+            assert_eq!(marketplace.pending_balance(accounts.bob), 100);
+            assert_eq!(
+                marketplace.cancel_swap(1),
+                Err(MarketplaceError::SwapNotFound)
+            );
+            assert_eq!(1, ink::env::test::recorded_events().count());
+        }
+
+        #[ink::test]
+        fn accept_swap_fails_if_token_not_given() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_swap(&mut marketplace, accounts.bob);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                marketplace.accept_swap(1, Some(Id::U128(2))),
+                Err(MarketplaceError::AlreadyOwner)
+            );
+
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.accept_swap(1, None),
+                Err(MarketplaceError::InvalidSwap)
+            );
+            assert_eq!(
+                marketplace.accept_swap(2, Some(Id::U128(2))),
+                Err(MarketplaceError::SwapNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn accept_swap_fails_if_expired() {
+            let mut marketplace = init_contract();
+            let accounts = default_accounts();
+            insert_swap(&mut marketplace, accounts.bob);
+
+            test::advance_block::<Environment>();
+            test::advance_block::<Environment>();
+            set_sender(accounts.charlie);
+            assert_eq!(
+                marketplace.accept_swap(1, Some(Id::U128(2))),
+                Err(MarketplaceError::SwapExpired)
+            );
+        }
+
+        fn insert_swap(marketplace: &mut MarketplaceContract, maker: AccountId) {
+            marketplace.marketplace.swaps.insert(
+                &1,
+                &Swap {
+                    maker,
+                    offered: vec![(contract_address(), Id::U128(1))],
+                    top_up: 100,
+                    target: SwapTarget::Collection(contract_address()),
+                    // Swap expires after two blocks.
+                    expiry: 12,
+                },
+            );
+        }
+
+        fn insert_bundle(
+            marketplace: &mut MarketplaceContract,
+            owner: AccountId,
+            weights: Vec<u32>,
+        ) {
+            let tokens: Vec<(AccountId, Id, u32)> = weights
+                .into_iter()
+                .enumerate()
+                .map(|(index, weight)| (contract_address(), Id::U128(index as u128 + 1), weight))
+                .collect();
+            for (contract, token_id, _) in tokens.iter() {
+                marketplace
+                    .marketplace
+                    .bundled_tokens
+                    .insert(&(*contract, token_id.clone()), &1);
+            }
+            marketplace.marketplace.bundles.insert(
+                &1,
+                &Bundle {
+                    owner,
+                    price: 100,
+                    tokens,
+                },
+            );
+        }
+
+        fn royalty_receivers() -> Vec<(AccountId, u16)> {
+            vec![(fee_recipient(), 10_000)]
+        }
+
+        fn register_contract(marketplace: &mut MarketplaceContract) {
+            assert!(marketplace
+                .register(
+                    contract_address(),
+                    royalty_receivers(),
+                    100,
+                    String::from("ipfs")
+                )
+                .is_ok());
+        }
+
+        fn insert_item(marketplace: &mut MarketplaceContract, owner: AccountId, price: Balance) {
+            marketplace.marketplace.items.insert(
+                &(contract_address(), Id::U128(1)),
+                &Item {
+                    owner,
+                    price,
+                    dutch_auction: None,
+                    starts_at: None,
+                    expires_at: None,
+                    currency: None,
+                    escrowed: false,
+                    reserved_for: None,
+                },
+            );
+        }
+
+        fn insert_auction(marketplace: &mut MarketplaceContract, seller: AccountId) {
+            // Auction ends after two blocks.
+            marketplace.marketplace.auctions.insert(
+                &(contract_address(), Id::U128(1)),
+                &Auction {
+                    seller,
+                    reserve_price: 100,
+                    start_time: 0,
+                    end_time: 12,
+                    highest_bidder: None,
+                    highest_bid: 0,
+                    referrer: None,
+                },
+            );
+        }
+
+        fn insert_sealed_auction(marketplace: &mut MarketplaceContract, seller: AccountId) {
+            // Commit phase ends after two blocks, reveal phase after four blocks.
+            marketplace.marketplace.sealed_auctions.insert(
+                &1,
+                &SealedAuction {
+                    contract: contract_address(),
+                    token_id: Id::U128(1),
+                    seller,
+                    reserve_price: 100,
+                    commit_end: 12,
+                    reveal_end: 24,
+                    second_price: true,
+                    bids: 0,
+                    highest_bidder: None,
+                    highest_bid: 0,
+                    second_bid: 0,
+                },
+            );
+            marketplace
+                .marketplace
+                .sealed_auction_ids
+                .insert(&(contract_address(), Id::U128(1)), &1);
+        }
+
+        fn sealed_bid_commitment(bidder: AccountId, bid: Balance, salt: Hash) -> Hash {
+            let mut output = <Blake2x256 as HashOutput>::Type::default();
+            ink::env::hash_encoded::<Blake2x256, _>(&(bidder, bid, salt), &mut output);
+            Hash::from(output)
+        }
+
         fn init_contract() -> MarketplaceContract {
             MarketplaceContract::new(fee_recipient())
         }
@@ -0,0 +1,48 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+#[openbrush::contract]
+pub mod test_psp22 {
+    // imports from openbrush
+    use openbrush::{
+        contracts::{
+            ownable::*,
+            psp22::extensions::mintable::*,
+        },
+        modifiers,
+        traits::Storage,
+    };
+
+    #[ink(storage)]
+    #[derive(Default, Storage)]
+    pub struct Contract {
+        #[storage_field]
+        psp22: psp22::Data,
+        #[storage_field]
+        ownable: ownable::Data,
+    }
+
+    impl PSP22 for Contract {}
+    impl Ownable for Contract {}
+
+    impl PSP22Mintable for Contract {
+        /// Mints tokens, only the contract owner is allowed to mint.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        fn mint(&mut self, account: AccountId, amount: Balance) -> Result<(), PSP22Error> {
+            self._mint_to(account, amount)
+        }
+    }
+
+    impl Contract {
+        #[ink(constructor)]
+        pub fn new(total_supply: Balance) -> Self {
+            let mut instance = Self::default();
+            instance._init_with_owner(instance.env().caller());
+            instance
+                ._mint_to(instance.env().caller(), total_supply)
+                .expect("Can mint");
+            instance
+        }
+    }
+}
@@ -32,6 +32,10 @@ pub mod rmrk_equippable {
         traits::*,
         types::*,
     };
+    use royalty_info::{
+        RoyaltyError,
+        RoyaltyInfo,
+    };
 
     /// Event emitted when a token transfer occurs.
     #[ink(event)]
@@ -211,6 +215,8 @@ pub mod rmrk_equippable {
         base: BaseData,
         #[storage_field]
         equippable: EquippableData,
+        #[storage_field]
+        royalty_info: royalty_info::Data,
     }
 
     impl PSP34 for Rmrk {}
@@ -221,6 +227,8 @@ pub mod rmrk_equippable {
 
     impl PSP34Enumerable for Rmrk {}
 
+    impl RoyaltyInfo for Rmrk {}
+
     impl MintingLazy for Rmrk {}
 
     impl Nesting for Rmrk {}
@@ -233,6 +241,16 @@ pub mod rmrk_equippable {
 
     impl Query for Rmrk {}
 
+    impl royalty_info::Internal for Rmrk {
+        /// Royalty info can only be changed by the collection admin.
+        fn _check_royalty_owner(&self) -> Result<(), RoyaltyError> {
+            if !self.has_role(DEFAULT_ADMIN_ROLE, self.env().caller()) {
+                return Err(RoyaltyError::NotOwner)
+            }
+            Ok(())
+        }
+    }
+
     impl Rmrk {
         /// Instantiate new RMRK contract
         #[allow(clippy::too_many_arguments)]
@@ -244,8 +262,8 @@ pub mod rmrk_equippable {
             max_supply: u64,
             price_per_mint: Balance,
             collection_metadata: String,
-            _royalty_receiver: AccountId,
-            _royalty: u8,
+            royalty_receiver: AccountId,
+            royalty: u16,
         ) -> Self {
             let mut instance = Rmrk::default();
             config::with_admin(&mut instance, Self::env().caller());
@@ -258,6 +276,8 @@ pub mod rmrk_equippable {
                 collection_metadata,
                 max_supply,
             );
+            instance.royalty_info.receiver = royalty_receiver;
+            instance.royalty_info.royalty = royalty;
             instance
         }
     }
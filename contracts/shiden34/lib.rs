@@ -26,6 +26,10 @@ pub mod shiden34 {
         impls::payable_mint::*,
         traits::payable_mint::*,
     };
+    use royalty_info::{
+        RoyaltyError,
+        RoyaltyInfo,
+    };
 
     // Shiden34Contract contract storage
     #[ink(storage)]
@@ -41,12 +45,15 @@ pub mod shiden34 {
         metadata: metadata::Data,
         #[storage_field]
         payable_mint: types::Data,
+        #[storage_field]
+        royalty_info: royalty_info::Data,
     }
 
     impl PSP34 for Shiden34Contract {}
     impl PSP34Enumerable for Shiden34Contract {}
     impl PSP34Metadata for Shiden34Contract {}
     impl Ownable for Shiden34Contract {}
+    impl RoyaltyInfo for Shiden34Contract {}
 
     /// Event emitted when a token transfer occurs.
     #[ink(event)]
@@ -79,6 +86,8 @@ pub mod shiden34 {
             base_uri: String,
             max_supply: u64,
             price_per_mint: Balance,
+            royalty_receiver: AccountId,
+            royalty: u16,
         ) -> Self {
             let mut instance = Self::default();
             instance._init_with_owner(instance.env().caller());
@@ -90,6 +99,8 @@ pub mod shiden34 {
             instance.payable_mint.price_per_mint = price_per_mint;
             instance.payable_mint.last_token_id = 0;
             instance.payable_mint.max_amount = 1;
+            instance.royalty_info.receiver = royalty_receiver;
+            instance.royalty_info.royalty = royalty;
             instance
         }
     }
@@ -118,6 +129,15 @@ pub mod shiden34 {
 
     impl PayableMint for Shiden34Contract {}
 
+    impl royalty_info::Internal for Shiden34Contract {
+        fn _check_royalty_owner(&self) -> Result<(), RoyaltyError> {
+            if self.env().caller() != self.owner() {
+                return Err(RoyaltyError::NotOwner)
+            }
+            Ok(())
+        }
+    }
+
     // ------------------- T E S T -----------------------------------------------------
     #[cfg(test)]
     mod tests {
@@ -137,6 +157,7 @@ pub mod shiden34 {
         const PRICE: Balance = 100_000_000_000_000_000;
         const BASE_URI: &str = "ipfs://myIpfsUri/";
         const MAX_SUPPLY: u64 = 10;
+        const ROYALTY: u16 = 250;
 
         #[ink::test]
         fn init_works() {
@@ -165,9 +186,48 @@ pub mod shiden34 {
                 String::from(BASE_URI),
                 MAX_SUPPLY,
                 PRICE,
+                default_accounts().alice,
+                ROYALTY,
             )
         }
 
+        #[ink::test]
+        fn royalty_info_works() {
+            let sh34 = init();
+            let accounts = default_accounts();
+
+            assert_eq!(sh34.royalty_info(Id::U64(1), 1000), (accounts.alice, 25));
+            assert_eq!(sh34.royalty_info(Id::U64(1), 39), (accounts.alice, 0));
+        }
+
+        #[ink::test]
+        fn set_royalty_info_works() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            assert!(sh34.set_royalty_info(accounts.bob, 500).is_ok());
+            assert_eq!(sh34.royalty_info(Id::U64(1), 1000), (accounts.bob, 50));
+            assert!(sh34.set_royalty_info(accounts.bob, 10_000).is_ok());
+            assert_eq!(sh34.royalty_info(Id::U64(1), 1000), (accounts.bob, 1000));
+            assert_eq!(
+                sh34.set_royalty_info(accounts.bob, 10_001),
+                Err(RoyaltyError::RoyaltyTooHigh)
+            );
+        }
+
+        #[ink::test]
+        fn set_royalty_info_fails_if_not_owner() {
+            let mut sh34 = init();
+            let accounts = default_accounts();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                sh34.set_royalty_info(accounts.bob, 500),
+                Err(RoyaltyError::NotOwner)
+            );
+            assert_eq!(sh34.royalty_info(Id::U64(1), 1000), (accounts.alice, 25));
+        }
+
         #[ink::test]
         fn mint_single_works() {
             let mut sh34 = init();
@@ -349,6 +409,8 @@ pub mod shiden34 {
                 String::from(BASE_URI),
                 max_supply,
                 PRICE,
+                default_accounts().alice,
+                ROYALTY,
             );
             sh34.payable_mint.last_token_id = max_supply - 1;
 
@@ -378,6 +440,8 @@ pub mod shiden34 {
                 String::from(BASE_URI),
                 max_supply,
                 price,
+                default_accounts().alice,
+                ROYALTY,
             );
             let transferred_value = u128::MAX;
             let mint_amount = u64::MAX;
@@ -0,0 +1,661 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::marketplace_sale::Internal;
+use crate::{
+    ensure,
+    impls::marketplace::types::{
+        Auction,
+        Data,
+        MarketplaceError,
+        SealedAuction,
+        SealedBid,
+    },
+    traits::auction::MarketplaceAuction,
+};
+use ink::env::hash::Blake2x256;
+use openbrush::{
+    contracts::{
+        ownable::*,
+        psp34::*,
+        reentrancy_guard::*,
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Balance,
+        Hash,
+        Storage,
+        Timestamp,
+    },
+};
+
+pub trait AuctionInternal {
+    /// Sells auctioned NFT to the winner for `price` and credits the rest of winner's `deposit`
+    /// to their pending balance. The whole deposit is credited back if seller no longer owns the token or the transfer fails.
+    fn sell_to_winner(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        seller: AccountId,
+        winner: AccountId,
+        price: Balance,
+        deposit: Balance,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+}
+
+pub trait AuctionEvents {
+    fn emit_auction_created_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    );
+    fn emit_bid_placed_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        bidder: AccountId,
+        amount: Balance,
+    );
+    fn emit_auction_settled_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        winner: Option<AccountId>,
+        price: Balance,
+    );
+    fn emit_auction_cancelled_event(&self, contract: AccountId, token_id: Id);
+    fn emit_sealed_auction_created_event(
+        &self,
+        auction_id: u64,
+        contract: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        commit_end: Timestamp,
+        reveal_end: Timestamp,
+        second_price: bool,
+    );
+    fn emit_sealed_bid_committed_event(&self, auction_id: u64, bidder: AccountId);
+    fn emit_sealed_bid_revealed_event(&self, auction_id: u64, bidder: AccountId, bid: Balance);
+}
+
+impl<T> MarketplaceAuction for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Puts a NFT up for an English auction on the marketplace.
+    default fn create_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            start_time < end_time && end_time > Self::env().block_timestamp(),
+            MarketplaceError::InvalidAuctionPeriod
+        );
+        ensure!(
+            !self.is_token_listed(contract_address, token_id.clone()),
+            MarketplaceError::ItemAlreadyListedForSale
+        );
+        ensure!(
+            !self.is_token_auctioned(contract_address, token_id.clone()),
+            MarketplaceError::AuctionAlreadyExists
+        );
+        self.check_token_owner(contract_address, token_id.clone())?;
+        self.check_marketplace_approval(contract_address, token_id.clone(), Self::env().caller())?;
+
+        self.data::<Data>().auctions.insert(
+            &(contract_address, token_id.clone()),
+            &Auction {
+                seller: Self::env().caller(),
+                reserve_price,
+                start_time,
+                end_time,
+                highest_bidder: None,
+                highest_bid: 0,
+                referrer: None,
+            },
+        );
+        self.emit_auction_created_event(
+            contract_address,
+            token_id,
+            reserve_price,
+            start_time,
+            end_time,
+        );
+        Ok(())
+    }
+
+    /// Places a bid on an auctioned NFT. Previous highest bid is refunded.
+    /// `referrer` of the winning bid receives the referral share of the marketplace fee.
+    #[modifiers(non_reentrant)]
+    default fn place_bid(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        let mut auction = self
+            .data::<Data>()
+            .auctions
+            .get(&(contract_address, token_id.clone()))
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+
+        let now = Self::env().block_timestamp();
+        ensure!(
+            now >= auction.start_time,
+            MarketplaceError::AuctionNotStarted
+        );
+        ensure!(now < auction.end_time, MarketplaceError::AuctionEnded);
+
+        let caller = Self::env().caller();
+        ensure!(caller != auction.seller, MarketplaceError::AlreadyOwner);
+        self.check_referrer(referrer, caller, auction.seller)?;
+
+        let value = Self::env().transferred_value();
+        ensure!(
+            value >= auction.reserve_price && value > auction.highest_bid,
+            MarketplaceError::BidTooLow
+        );
+
+        // Outbid amount is credited to the previous bidder so a bidder that can't
+        // receive funds is not able to block new bids.
+        if let Some(previous_bidder) = auction.highest_bidder {
+            self.add_pending_balance(previous_bidder, auction.highest_bid);
+        }
+
+        auction.highest_bidder = Some(caller);
+        auction.highest_bid = value;
+        auction.referrer = referrer;
+        self.data::<Data>()
+            .auctions
+            .insert(&(contract_address, token_id.clone()), &auction);
+        self.emit_bid_placed_event(contract_address, token_id, caller, value);
+        Ok(())
+    }
+
+    /// Settles an ended auction, transferring NFT to the highest bidder.
+    #[modifiers(non_reentrant)]
+    default fn settle_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let auction = self
+            .data::<Data>()
+            .auctions
+            .get(&(contract_address, token_id.clone()))
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() >= auction.end_time,
+            MarketplaceError::AuctionNotEnded
+        );
+
+        self.data::<Data>()
+            .auctions
+            .remove(&(contract_address, token_id.clone()));
+
+        let winner = match auction.highest_bidder {
+            Some(winner) => winner,
+            None => {
+                self.emit_auction_settled_event(contract_address, token_id, None, 0);
+                return Ok(())
+            }
+        };
+
+        self.sell_to_winner(
+            contract_address,
+            token_id,
+            auction.seller,
+            winner,
+            auction.highest_bid,
+            auction.highest_bid,
+            auction.referrer,
+        )
+    }
+
+    /// Cancels an auction which has not received any bids yet.
+    default fn cancel_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let auction = self
+            .data::<Data>()
+            .auctions
+            .get(&(contract_address, token_id.clone()))
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            auction.seller == Self::env().caller(),
+            MarketplaceError::NotOwner
+        );
+        ensure!(
+            auction.highest_bidder.is_none(),
+            MarketplaceError::AuctionHasBids
+        );
+
+        self.data::<Data>()
+            .auctions
+            .remove(&(contract_address, token_id.clone()));
+        self.emit_auction_cancelled_event(contract_address, token_id);
+        Ok(())
+    }
+
+    /// Gets an auction for the NFT.
+    default fn get_auction(&self, contract_address: AccountId, token_id: Id) -> Option<Auction> {
+        self.data::<Data>()
+            .auctions
+            .get(&(contract_address, token_id))
+    }
+
+    /// Puts a NFT up for a sealed-bid auction. Bids are committed until `commit_end`
+    /// and revealed until `reveal_end`. With `second_price` set the winner pays the
+    /// second highest revealed bid (or the reserve price) instead of their own bid.
+    default fn create_sealed_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        commit_end: Timestamp,
+        reveal_end: Timestamp,
+        second_price: bool,
+    ) -> Result<u64, MarketplaceError> {
+        ensure!(
+            commit_end > Self::env().block_timestamp() && commit_end < reveal_end,
+            MarketplaceError::InvalidAuctionPeriod
+        );
+        ensure!(
+            !self.is_token_listed(contract_address, token_id.clone()),
+            MarketplaceError::ItemAlreadyListedForSale
+        );
+        ensure!(
+            !self.is_token_auctioned(contract_address, token_id.clone()),
+            MarketplaceError::AuctionAlreadyExists
+        );
+        self.check_token_owner(contract_address, token_id.clone())?;
+        self.check_marketplace_approval(contract_address, token_id.clone(), Self::env().caller())?;
+
+        let auction_id = self.data::<Data>().last_sealed_auction_id.saturating_add(1);
+        self.data::<Data>().sealed_auctions.insert(
+            &auction_id,
+            &SealedAuction {
+                contract: contract_address,
+                token_id: token_id.clone(),
+                seller: Self::env().caller(),
+                reserve_price,
+                commit_end,
+                reveal_end,
+                second_price,
+                bids: 0,
+                highest_bidder: None,
+                highest_bid: 0,
+                second_bid: 0,
+            },
+        );
+        self.data::<Data>()
+            .sealed_auction_ids
+            .insert(&(contract_address, token_id.clone()), &auction_id);
+        self.data::<Data>().last_sealed_auction_id = auction_id;
+
+        self.emit_sealed_auction_created_event(
+            auction_id,
+            contract_address,
+            token_id,
+            reserve_price,
+            commit_end,
+            reveal_end,
+            second_price,
+        );
+        Ok(auction_id)
+    }
+
+    /// Commits a sealed bid. `commitment` is a `Blake2x256` hash of SCALE encoded
+    /// `(bidder, bid, salt)` and transferred value is held as a deposit covering the bid.
+    /// `referrer` of the winning bid receives the referral share of the marketplace fee.
+    default fn commit_sealed_bid(
+        &mut self,
+        auction_id: u64,
+        commitment: Hash,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        let mut auction = self
+            .data::<Data>()
+            .sealed_auctions
+            .get(&auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() < auction.commit_end,
+            MarketplaceError::CommitPhaseEnded
+        );
+
+        let caller = Self::env().caller();
+        ensure!(caller != auction.seller, MarketplaceError::AlreadyOwner);
+        ensure!(
+            !self
+                .data::<Data>()
+                .sealed_bids
+                .contains(&(auction_id, caller)),
+            MarketplaceError::BidAlreadyCommitted
+        );
+        self.check_referrer(referrer, caller, auction.seller)?;
+
+        // Deposit hides the bid, so it only has to cover the reserve price.
+        let deposit = Self::env().transferred_value();
+        ensure!(
+            deposit > 0 && deposit >= auction.reserve_price,
+            MarketplaceError::BidTooLow
+        );
+
+        self.data::<Data>().sealed_bids.insert(
+            &(auction_id, caller),
+            &SealedBid {
+                commitment,
+                deposit,
+                revealed: false,
+                referrer,
+            },
+        );
+        auction.bids = auction.bids.saturating_add(1);
+        self.data::<Data>()
+            .sealed_auctions
+            .insert(&auction_id, &auction);
+
+        self.emit_sealed_bid_committed_event(auction_id, caller);
+        Ok(())
+    }
+
+    /// Reveals a committed sealed bid.
+    default fn reveal_sealed_bid(
+        &mut self,
+        auction_id: u64,
+        bid: Balance,
+        salt: Hash,
+    ) -> Result<(), MarketplaceError> {
+        let mut auction = self
+            .data::<Data>()
+            .sealed_auctions
+            .get(&auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        let now = Self::env().block_timestamp();
+        ensure!(
+            now >= auction.commit_end && now < auction.reveal_end,
+            MarketplaceError::RevealPhaseNotActive
+        );
+
+        let caller = Self::env().caller();
+        let mut sealed_bid = self
+            .data::<Data>()
+            .sealed_bids
+            .get(&(auction_id, caller))
+            .ok_or(MarketplaceError::BidNotFound)?;
+        ensure!(!sealed_bid.revealed, MarketplaceError::BidNotFound);
+
+        let hash = Self::env().hash_encoded::<Blake2x256, _>(&(caller, bid, salt));
+        ensure!(
+            Hash::from(hash) == sealed_bid.commitment
+                && bid <= sealed_bid.deposit
+                && bid >= auction.reserve_price,
+            MarketplaceError::InvalidReveal
+        );
+
+        // Equal bids are won by the earlier reveal.
+        if auction.highest_bidder.is_none() || bid > auction.highest_bid {
+            if auction.highest_bidder.is_some() {
+                auction.second_bid = auction.highest_bid;
+            }
+            auction.highest_bidder = Some(caller);
+            auction.highest_bid = bid;
+        } else if bid > auction.second_bid {
+            auction.second_bid = bid;
+        }
+        sealed_bid.revealed = true;
+        self.data::<Data>()
+            .sealed_bids
+            .insert(&(auction_id, caller), &sealed_bid);
+        self.data::<Data>()
+            .sealed_auctions
+            .insert(&auction_id, &auction);
+
+        self.emit_sealed_bid_revealed_event(auction_id, caller, bid);
+        Ok(())
+    }
+
+    /// Settles a sealed auction after the reveal phase, transferring NFT to the highest bidder.
+    #[modifiers(non_reentrant)]
+    default fn settle_sealed_auction(&mut self, auction_id: u64) -> Result<(), MarketplaceError> {
+        let auction = self
+            .data::<Data>()
+            .sealed_auctions
+            .get(&auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() >= auction.reveal_end,
+            MarketplaceError::AuctionNotEnded
+        );
+
+        self.data::<Data>().sealed_auctions.remove(&auction_id);
+        self.data::<Data>()
+            .sealed_auction_ids
+            .remove(&(auction.contract, auction.token_id.clone()));
+
+        let winner = match auction.highest_bidder {
+            Some(winner) => winner,
+            None => {
+                self.emit_auction_settled_event(auction.contract, auction.token_id, None, 0);
+                return Ok(())
+            }
+        };
+
+        let winning_bid = self
+            .data::<Data>()
+            .sealed_bids
+            .get(&(auction_id, winner))
+            .ok_or(MarketplaceError::BidNotFound)?;
+        self.data::<Data>()
+            .sealed_bids
+            .remove(&(auction_id, winner));
+
+        let price = if auction.second_price {
+            auction.second_bid.max(auction.reserve_price)
+        } else {
+            auction.highest_bid
+        };
+        self.sell_to_winner(
+            auction.contract,
+            auction.token_id,
+            auction.seller,
+            winner,
+            price,
+            winning_bid.deposit,
+            winning_bid.referrer,
+        )
+    }
+
+    /// Cancels a sealed auction which has not received any bid commitments yet.
+    default fn cancel_sealed_auction(&mut self, auction_id: u64) -> Result<(), MarketplaceError> {
+        let auction = self
+            .data::<Data>()
+            .sealed_auctions
+            .get(&auction_id)
+            .ok_or(MarketplaceError::AuctionNotFound)?;
+        ensure!(
+            auction.seller == Self::env().caller(),
+            MarketplaceError::NotOwner
+        );
+        ensure!(auction.bids == 0, MarketplaceError::AuctionHasBids);
+
+        self.data::<Data>().sealed_auctions.remove(&auction_id);
+        self.data::<Data>()
+            .sealed_auction_ids
+            .remove(&(auction.contract, auction.token_id.clone()));
+        self.emit_auction_cancelled_event(auction.contract, auction.token_id);
+        Ok(())
+    }
+
+    /// Credits the deposit of a bid which has not won a sealed auction
+    /// to the bidder's pending balance.
+    #[modifiers(non_reentrant)]
+    default fn withdraw_sealed_bid(&mut self, auction_id: u64) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let sealed_bid = self
+            .data::<Data>()
+            .sealed_bids
+            .get(&(auction_id, caller))
+            .ok_or(MarketplaceError::BidNotFound)?;
+
+        // Deposits are released once the reveal phase ends. Winning deposit is
+        // released by the settlement.
+        if let Some(auction) = self.data::<Data>().sealed_auctions.get(&auction_id) {
+            ensure!(
+                Self::env().block_timestamp() >= auction.reveal_end
+                    && auction.highest_bidder != Some(caller),
+                MarketplaceError::AuctionNotEnded
+            );
+        }
+
+        self.data::<Data>()
+            .sealed_bids
+            .remove(&(auction_id, caller));
+        self.add_pending_balance(caller, sealed_bid.deposit);
+        Ok(())
+    }
+
+    /// Gets a sealed auction.
+    default fn get_sealed_auction(&self, auction_id: u64) -> Option<SealedAuction> {
+        self.data::<Data>().sealed_auctions.get(&auction_id)
+    }
+
+    /// Gets a bid committed to a sealed auction.
+    default fn get_sealed_bid(&self, auction_id: u64, bidder: AccountId) -> Option<SealedBid> {
+        self.data::<Data>().sealed_bids.get(&(auction_id, bidder))
+    }
+}
+
+impl<T> AuctionInternal for T
+where
+    T: Storage<Data>,
+{
+    default fn sell_to_winner(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        seller: AccountId,
+        winner: AccountId,
+        price: Balance,
+        deposit: Balance,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        // Seller could have transferred the token or revoked marketplace approval
+        // during the auction. In that case the winning bid is returned.
+        let sale = match PSP34Ref::owner_of(&contract_address, token_id.clone()) {
+            Some(token_owner) if token_owner == seller => {
+                self.sell_token(
+                    contract_address,
+                    token_id.clone(),
+                    seller,
+                    winner,
+                    price,
+                    None,
+                    referrer,
+                )
+            }
+            _ => Err(MarketplaceError::UnableToTransferToken),
+        };
+
+        match sale {
+            Ok(()) => {
+                self.add_pending_balance(winner, deposit - price);
+                self.emit_auction_settled_event(contract_address, token_id, Some(winner), price);
+                Ok(())
+            }
+            Err(MarketplaceError::UnableToTransferToken) => {
+                self.add_pending_balance(winner, deposit);
+                self.emit_auction_cancelled_event(contract_address, token_id);
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+impl<T> AuctionEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_auction_created_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _reserve_price: Balance,
+        _start_time: Timestamp,
+        _end_time: Timestamp,
+    ) {
+    }
+
+    default fn emit_bid_placed_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _bidder: AccountId,
+        _amount: Balance,
+    ) {
+    }
+
+    default fn emit_auction_settled_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _winner: Option<AccountId>,
+        _price: Balance,
+    ) {
+    }
+
+    default fn emit_auction_cancelled_event(&self, _contract: AccountId, _token_id: Id) {}
+
+    default fn emit_sealed_auction_created_event(
+        &self,
+        _auction_id: u64,
+        _contract: AccountId,
+        _token_id: Id,
+        _reserve_price: Balance,
+        _commit_end: Timestamp,
+        _reveal_end: Timestamp,
+        _second_price: bool,
+    ) {
+    }
+
+    default fn emit_sealed_bid_committed_event(&self, _auction_id: u64, _bidder: AccountId) {}
+
+    default fn emit_sealed_bid_revealed_event(
+        &self,
+        _auction_id: u64,
+        _bidder: AccountId,
+        _bid: Balance,
+    ) {
+    }
+}
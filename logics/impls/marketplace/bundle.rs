@@ -0,0 +1,281 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::marketplace_sale::Internal;
+use crate::{
+    ensure,
+    impls::marketplace::types::{
+        Bundle,
+        Data,
+        MarketplaceError,
+        MAX_BUNDLE_SIZE,
+    },
+    traits::bundle::MarketplaceBundle,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{
+        ownable::*,
+        psp34::*,
+        reentrancy_guard::*,
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+    },
+};
+
+pub trait BundleInternal {
+    /// Removes bundle and releases its tokens.
+    fn remove_bundle(&mut self, bundle_id: u64);
+
+    /// Checks if all bundle tokens are still owned by the bundle seller.
+    fn is_bundle_valid(&self, bundle: &Bundle) -> bool;
+
+    /// Apportions price between bundle tokens according to their weights.
+    /// Rounding remainder goes to the first token. Fails if a share overflows.
+    fn bundle_shares(
+        &self,
+        bundle: &Bundle,
+        price: Balance,
+    ) -> Result<Vec<Balance>, MarketplaceError>;
+}
+
+pub trait BundleEvents {
+    fn emit_bundle_listed_event(&self, bundle_id: u64, owner: AccountId, price: Balance);
+    fn emit_bundle_unlisted_event(&self, bundle_id: u64);
+    fn emit_bundle_bought_event(&self, bundle_id: u64, buyer: AccountId, price: Balance);
+}
+
+impl<T> MarketplaceBundle for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Creates a sale of several NFTs for a single price. Each NFT is paired with
+    /// a weight used to apportion the price, the price is split evenly if all weights are zero.
+    default fn list_bundle(
+        &mut self,
+        tokens: Vec<(AccountId, Id, u32)>,
+        price: Balance,
+    ) -> Result<u64, MarketplaceError> {
+        ensure!(
+            tokens.len() >= 2 && tokens.len() <= MAX_BUNDLE_SIZE,
+            MarketplaceError::InvalidBundleSize
+        );
+
+        for (index, (contract_address, token_id, _)) in tokens.iter().enumerate() {
+            ensure!(
+                !tokens[..index]
+                    .iter()
+                    .any(|(contract, id, _)| contract == contract_address && id == token_id),
+                MarketplaceError::InvalidBundleSize
+            );
+            let key = (*contract_address, token_id.clone());
+            ensure!(
+                !self.data::<Data>().bundled_tokens.contains(&key),
+                MarketplaceError::TokenAlreadyInBundle
+            );
+            ensure!(
+                !self.is_token_auctioned(*contract_address, token_id.clone()),
+                MarketplaceError::AuctionAlreadyExists
+            );
+            ensure!(
+                !self.is_token_listed(*contract_address, token_id.clone()),
+                MarketplaceError::ItemAlreadyListedForSale
+            );
+            self.check_token_owner(*contract_address, token_id.clone())?;
+            self.check_marketplace_approval(
+                *contract_address,
+                token_id.clone(),
+                Self::env().caller(),
+            )?;
+        }
+
+        let bundle = Bundle {
+            owner: Self::env().caller(),
+            price,
+            tokens,
+        };
+        // A bundle whose price can not be apportioned could never be bought.
+        self.bundle_shares(&bundle, price)?;
+
+        let owner = bundle.owner;
+        let bundle_id = self.data::<Data>().last_bundle_id.saturating_add(1);
+        for (contract_address, token_id, _) in bundle.tokens.iter() {
+            self.data::<Data>()
+                .bundled_tokens
+                .insert(&(*contract_address, token_id.clone()), &bundle_id);
+        }
+        self.data::<Data>().bundles.insert(&bundle_id, &bundle);
+        self.data::<Data>().last_bundle_id = bundle_id;
+
+        self.emit_bundle_listed_event(bundle_id, owner, price);
+        Ok(bundle_id)
+    }
+
+    /// Removes a bundle from the marketplace sale. Bundle can be removed by anyone
+    /// once some of its NFTs are no longer owned by the seller.
+    default fn unlist_bundle(&mut self, bundle_id: u64) -> Result<(), MarketplaceError> {
+        let bundle = self
+            .data::<Data>()
+            .bundles
+            .get(&bundle_id)
+            .ok_or(MarketplaceError::BundleNotFound)?;
+        ensure!(
+            bundle.owner == Self::env().caller() || !self.is_bundle_valid(&bundle),
+            MarketplaceError::NotOwner
+        );
+
+        self.remove_bundle(bundle_id);
+        self.emit_bundle_unlisted_event(bundle_id);
+        Ok(())
+    }
+
+    /// Buys all NFTs from a bundle. Value transferred above the bundle price is
+    /// credited to the buyer's pending balance.
+    #[modifiers(non_reentrant)]
+    default fn buy_bundle(&mut self, bundle_id: u64) -> Result<(), MarketplaceError> {
+        let bundle = self
+            .data::<Data>()
+            .bundles
+            .get(&bundle_id)
+            .ok_or(MarketplaceError::BundleNotFound)?;
+
+        let caller = Self::env().caller();
+        ensure!(caller != bundle.owner, MarketplaceError::AlreadyOwner);
+
+        // Only the bundle price is charged, any surplus is returned to the buyer.
+        let surplus = self.check_price(Self::env().transferred_value(), bundle.price)?;
+        ensure!(
+            self.is_bundle_valid(&bundle),
+            MarketplaceError::BundleInvalidated
+        );
+
+        let shares = self.bundle_shares(&bundle, bundle.price)?;
+        self.remove_bundle(bundle_id);
+        for ((contract_address, token_id, _), share) in bundle.tokens.into_iter().zip(shares) {
+            self.sell_token(
+                contract_address,
+                token_id,
+                bundle.owner,
+                caller,
+                share,
+                None,
+                None,
+            )?;
+        }
+        self.add_pending_balance(caller, surplus);
+
+        self.emit_bundle_bought_event(bundle_id, caller, bundle.price);
+        Ok(())
+    }
+
+    /// Gets a bundle.
+    default fn get_bundle(&self, bundle_id: u64) -> Option<Bundle> {
+        self.data::<Data>().bundles.get(&bundle_id)
+    }
+
+    /// Gets id of a bundle containing the NFT.
+    default fn get_token_bundle(&self, contract_address: AccountId, token_id: Id) -> Option<u64> {
+        self.data::<Data>()
+            .bundled_tokens
+            .get(&(contract_address, token_id))
+    }
+}
+
+impl<T> BundleInternal for T
+where
+    T: Storage<Data>,
+{
+    default fn remove_bundle(&mut self, bundle_id: u64) {
+        if let Some(bundle) = self.data::<Data>().bundles.get(&bundle_id) {
+            for (contract_address, token_id, _) in bundle.tokens {
+                self.data::<Data>()
+                    .bundled_tokens
+                    .remove(&(contract_address, token_id));
+            }
+            self.data::<Data>().bundles.remove(&bundle_id);
+        }
+    }
+
+    default fn is_bundle_valid(&self, bundle: &Bundle) -> bool {
+        bundle.tokens.iter().all(|(contract_address, token_id, _)| {
+            PSP34Ref::owner_of(contract_address, token_id.clone()) == Some(bundle.owner)
+        })
+    }
+
+    default fn bundle_shares(
+        &self,
+        bundle: &Bundle,
+        price: Balance,
+    ) -> Result<Vec<Balance>, MarketplaceError> {
+        let total_weight: u128 = bundle
+            .tokens
+            .iter()
+            .map(|(_, _, weight)| *weight as u128)
+            .sum();
+
+        let mut shares = Vec::with_capacity(bundle.tokens.len());
+        for (_, _, weight) in bundle.tokens.iter() {
+            let share = if total_weight == 0 {
+                price / bundle.tokens.len() as u128
+            } else {
+                price
+                    .checked_mul(*weight as u128)
+                    .ok_or(MarketplaceError::BadBuyValue)?
+                    / total_weight
+            };
+            shares.push(share);
+        }
+
+        // Rounding remainder goes to the first token, the same as for royalty receivers.
+        let distributed: Balance = shares.iter().sum();
+        if let Some(first) = shares.first_mut() {
+            *first += price - distributed;
+        }
+        Ok(shares)
+    }
+}
+
+impl<T> BundleEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_bundle_listed_event(
+        &self,
+        _bundle_id: u64,
+        _owner: AccountId,
+        _price: Balance,
+    ) {
+    }
+
+    default fn emit_bundle_unlisted_event(&self, _bundle_id: u64) {}
+
+    default fn emit_bundle_bought_event(
+        &self,
+        _bundle_id: u64,
+        _buyer: AccountId,
+        _price: Balance,
+    ) {
+    }
+}
@@ -19,26 +19,42 @@
 // OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
 // WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
-use super::types::{
-    NftContractType,
-    RegisteredCollection,
+use super::{
+    bundle::{
+        BundleEvents,
+        BundleInternal,
+    },
+    types::{
+        NftContractType,
+        RegisteredCollection,
+    },
 };
 use crate::{
     ensure,
     impls::marketplace::types::{
         Data,
+        DutchAuction,
         Item,
+        ListingOptions,
         MarketplaceError,
+        MAX_DISCOUNT_TIERS,
+        MAX_FEE_CHANGE_DELAY,
+        MAX_ROYALTY_RECEIVERS,
     },
     traits::marketplace::MarketplaceSale,
 };
 use ink::{
-    env::hash::Blake2x256,
+    env::{
+        hash::Blake2x256,
+        CallFlags,
+    },
+    prelude::vec::Vec,
     ToAccountId,
 };
 use openbrush::{
     contracts::{
         ownable::*,
+        psp22::PSP22Ref,
         psp34::*,
         reentrancy_guard::*,
     },
@@ -49,9 +65,11 @@ use openbrush::{
         Hash,
         Storage,
         String,
+        Timestamp,
     },
 };
 use rmrk_equippable::rmrk_equippable::RmrkRef;
+use royalty_info::RoyaltyInfoRef;
 use shiden34::shiden34::Shiden34ContractRef;
 
 pub trait Internal {
@@ -62,33 +80,225 @@ pub trait Internal {
         token_id: Id,
     ) -> Result<(), MarketplaceError>;
 
-    /// Checks token price.
+    /// Checks if token is listed and can be bought by the buyer.
+    /// Returns the listing and the current token owner.
+    fn check_listing(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+    ) -> Result<(Item, AccountId), MarketplaceError>;
+
+    /// Checks if listed token is still owned by the lister, or held in escrow for them.
+    /// Returns the lister.
+    fn check_listing_owner(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        item: &Item,
+    ) -> Result<AccountId, MarketplaceError>;
+
+    /// Checks if listed token can be bought by the buyer for at most `max_price`
+    /// and its PSP22 currency is still allowed.
+    /// Returns the listing, the current token owner and the current price.
+    fn check_purchase(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+        max_price: Option<Balance>,
+    ) -> Result<(Item, AccountId, Balance), MarketplaceError>;
+
+    /// Sells token to the buyer and removes its listing and a bundle containing it.
+    fn sell_and_delist_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Option<AccountId>,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Removes listing of a token which has changed hands,
+    /// together with a bundle containing the token.
+    fn delist_token(&mut self, contract_address: AccountId, token_id: Id);
+
+    /// Buys listed token for the caller. Fails if current token price exceeds `max_price`.
+    fn buy_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        max_price: Option<Balance>,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks if transferred value covers token price and returns the overpaid amount.
     fn check_price(
         &self,
         transferred_value: Balance,
         price: Balance,
-    ) -> Result<(), MarketplaceError>;
+    ) -> Result<Balance, MarketplaceError>;
+
+    /// Gets current item price, taking dutch auction price decay into account.
+    fn item_price(&self, item: &Item) -> Balance;
 
     /// Checks fee
     fn check_fee(&self, fee: u16, max_fee: u16) -> Result<(), MarketplaceError>;
 
-    /// Checks if token is listed for sale on the marketplace.
+    /// Checks if listing has started and has not expired.
+    fn check_listing_active(&self, item: &Item) -> Result<(), MarketplaceError>;
+
+    /// Checks if token is listed for sale on the marketplace. Expired listings are not considered.
     fn is_token_listed(&self, contract_address: AccountId, token_id: Id) -> bool;
 
+    /// Checks if token is put up for an English or a sealed-bid auction.
+    fn is_token_auctioned(&self, contract_address: AccountId, token_id: Id) -> bool;
+
     /// Transfers token.
     fn transfer_token(
-        &self,
+        &mut self,
         contract_address: AccountId,
         token_id: Id,
         token_owner: AccountId,
         buyer: AccountId,
         seller_fee: Balance,
         marketplace_fee: Balance,
-        royalty_receiver: AccountId,
+        referral: Option<(AccountId, Balance)>,
+        royalty_receivers: Vec<(AccountId, u16)>,
         author_royalty: Balance,
         token_price: Balance,
+        currency: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers token to the buyer and splits the price between the token owner,
+    /// the marketplace fee recipient, the referrer and the collection royalty receivers.
+    fn sell_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Option<AccountId>,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Splits price into seller amount, marketplace fee, referral fee and collection royalty.
+    /// Returns the amounts together with the referrer and the royalty receivers.
+    /// Marketplace fee is reduced by `fee_discount` basis points and referral fee is
+    /// a share of the reduced marketplace fee.
+    /// Royalty reported by the NFT contract is preferred over the registered one
+    /// unless `has_royalty_override` is set, and is capped by the max fee.
+    fn split_price(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        referrer: Option<AccountId>,
+        fee_discount: u16,
+        nft_royalty: Option<(AccountId, Balance)>,
+    ) -> Result<
+        (
+            Balance,
+            Balance,
+            Option<(AccountId, Balance)>,
+            Vec<(AccountId, u16)>,
+            Balance,
+        ),
+        MarketplaceError,
+    >;
+
+    /// Queries royalty info of the NFT contract. Returns `None` if the contract
+    /// doesn't implement `RoyaltyInfo`.
+    fn nft_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Option<(AccountId, Balance)>;
+
+    /// Gets marketplace fee of the collection, falling back to the global fee if not overridden.
+    fn collection_fee(&self, contract_address: AccountId) -> u16;
+
+    /// Gets fee discount of the account by the number of membership tokens it holds.
+    fn fee_discount(&self, account: AccountId) -> u16;
+
+    /// Gets discount of the highest tier reached by holding `tokens` membership tokens.
+    fn tier_discount(&self, tokens: u32) -> u16;
+
+    /// Checks that referrer is neither the buyer nor the seller.
+    fn check_referrer(
+        &self,
+        referrer: Option<AccountId>,
+        buyer: AccountId,
+        seller: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks if registered royalty takes precedence over the NFT contract royalty, which
+    /// holds for tokens with overridden royalty and collections with several royalty receivers.
+    fn has_royalty_override(&self, contract_address: AccountId, token_id: Id) -> bool;
+
+    /// Gets royalty of the token, falling back to the collection royalty if not overridden.
+    fn token_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        collection: &RegisteredCollection,
+    ) -> u16;
+
+    /// Splits `royalty` between royalty receivers by their shares.
+    /// Rounding remainder goes to the first receiver.
+    fn royalty_shares(
+        &self,
+        royalty_receivers: &[(AccountId, u16)],
+        royalty: Balance,
+    ) -> Vec<(AccountId, Balance)>;
+
+    /// Checks if royalty receivers are not empty, bounded and their shares sum to 10 000.
+    fn check_royalty_receivers(
+        &self,
+        royalty_receivers: &[(AccountId, u16)],
+    ) -> Result<(), MarketplaceError>;
+
+    /// Pays `amount` to `to`. Native currency is credited to the recipient's pending balance
+    /// to be withdrawn later, PSP22 tokens are transferred directly from the buyer.
+    fn transfer_funds(
+        &mut self,
+        currency: Option<AccountId>,
+        buyer: AccountId,
+        to: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Checks if marketplace is approved to transfer the token on behalf of its owner,
+    /// either for this token only or for all owner's tokens.
+    fn check_marketplace_approval(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        owner: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Transfers token from or into the marketplace escrow.
+    fn transfer_escrowed_token(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        to: AccountId,
     ) -> Result<(), MarketplaceError>;
 
+    /// Sets or removes marketplace fee override of the collection.
+    fn update_collection_fee(&mut self, contract_address: AccountId, fee: Option<u16>);
+
+    /// Credits native proceeds or refund to the account's pending balance.
+    /// Native funds are never pushed to accounts, so a rejecting recipient can't block a call.
+    fn add_pending_balance(&mut self, account: AccountId, amount: Balance);
+
+    /// Checks if PSP22 token is allowed as a listing currency.
+    fn check_currency(&self, currency: Option<AccountId>) -> Result<(), MarketplaceError>;
+
     /// Get NFT contract hash needed for factory method
     fn get_nft_contract_hash(
         &self,
@@ -97,9 +307,57 @@ pub trait Internal {
 }
 
 pub trait MarketplaceSaleEvents {
-    fn emit_token_listed_event(&self, contract: AccountId, token_id: Id, price: Option<Balance>);
-    fn emit_token_bought_event(&self, contract: AccountId, token_id: Id, price: Balance);
+    fn emit_token_listed_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        price: Option<Balance>,
+        currency: Option<AccountId>,
+        reserved_for: Option<AccountId>,
+    );
+    fn emit_token_bought_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        price: Balance,
+        currency: Option<AccountId>,
+    );
+    fn emit_listing_price_updated_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        old_price: Balance,
+        new_price: Balance,
+    );
+    fn emit_dutch_auction_listed_event(
+        &self,
+        contract: AccountId,
+        token_id: Id,
+        start_price: Balance,
+        end_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    );
     fn emit_collection_registered_event(&self, contract: AccountId);
+    fn emit_token_royalty_set_event(&self, contract: AccountId, token_id: Id, royalty: Option<u16>);
+    fn emit_collection_fee_set_event(&self, contract: AccountId, fee: Option<u16>);
+    fn emit_collection_fee_change_proposed_event(
+        &self,
+        contract: AccountId,
+        fee: Option<u16>,
+        executable_at: Timestamp,
+    );
+    fn emit_collection_fee_change_cancelled_event(&self, contract: AccountId, fee: Option<u16>);
+    fn emit_fee_change_proposed_event(
+        &self,
+        fee: Option<u16>,
+        fee_recipient: Option<AccountId>,
+        executable_at: Timestamp,
+    );
+    fn emit_fee_change_executed_event(&self, fee: Option<u16>, fee_recipient: Option<AccountId>);
+    fn emit_fee_change_cancelled_event(&self, fee: Option<u16>, fee_recipient: Option<AccountId>);
+    fn emit_listing_invalidated_event(&self, contract: AccountId, token_id: Id, owner: AccountId);
+    fn emit_proceeds_withdrawn_event(&self, account: AccountId, to: AccountId, amount: Balance);
 }
 
 impl<T> MarketplaceSale for T
@@ -107,10 +365,13 @@ where
     T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
 {
     /// Adds a NFT contract to the marketplace.
+    /// NFT contract supports a single royalty receiver, so it reports the whole `royalty`
+    /// for the first of `royalty_receivers`. Sales on the marketplace split the royalty
+    /// between all registered receivers instead.
     default fn factory(
         &mut self,
         marketplace_ipfs: String,
-        royalty_receiver: AccountId,
+        royalty_receivers: Vec<(AccountId, u16)>,
         royalty: u16,
         nft_name: String,
         nft_symbol: String,
@@ -119,6 +380,7 @@ where
         nft_price_per_mint: Balance,
         nft_contract_type: NftContractType,
     ) -> Result<AccountId, MarketplaceError> {
+        self.check_royalty_receivers(&royalty_receivers)?;
         let contract_hash = self.get_nft_contract_hash(&nft_contract_type)?;
 
         // Generate salt
@@ -134,6 +396,9 @@ where
                     nft_base_uri,
                     nft_max_supply,
                     nft_price_per_mint,
+                    // NFT contracts support a single royalty receiver.
+                    royalty_receivers[0].0,
+                    royalty,
                 )
                 .endowment(0)
                 .code_hash(contract_hash)
@@ -153,8 +418,9 @@ where
                     nft_max_supply,
                     nft_price_per_mint,
                     nft_base_uri,
-                    royalty_receiver,
-                    (royalty / 100) as u8,
+                    // NFT contracts support a single royalty receiver.
+                    royalty_receivers[0].0,
+                    royalty,
                 )
                 .endowment(0)
                 .code_hash(contract_hash)
@@ -171,7 +437,7 @@ where
         self.data::<Data>().registered_collections.insert(
             &contract_address,
             &RegisteredCollection {
-                royalty_receiver,
+                royalty_receivers,
                 royalty,
                 marketplace_ipfs,
             },
@@ -208,105 +474,388 @@ where
         token_id: Id,
         price: Balance,
     ) -> Result<(), MarketplaceError> {
+        self.list_with_options(contract_address, token_id, price, ListingOptions::default())
+    }
+
+    /// Creates a NFT item sale on the marketplace. Sale can optionally be
+    /// scheduled, priced in an allowed PSP22 token, escrowed by the marketplace
+    /// or reserved for a single buyer, see `ListingOptions`.
+    default fn list_with_options(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        options: ListingOptions,
+    ) -> Result<(), MarketplaceError> {
+        let ListingOptions {
+            starts_at,
+            expires_at,
+            currency,
+            escrow,
+            reserved_for,
+        } = options;
+        self.check_currency(currency)?;
+        if let Some(expires_at) = expires_at {
+            ensure!(
+                expires_at > Self::env().block_timestamp()
+                    && starts_at.unwrap_or_default() < expires_at,
+                MarketplaceError::InvalidListingPeriod
+            );
+        }
         ensure!(
             !self.is_token_listed(contract_address, token_id.clone()),
             MarketplaceError::ItemAlreadyListedForSale
         );
+        ensure!(
+            !self.is_token_auctioned(contract_address, token_id.clone()),
+            MarketplaceError::AuctionAlreadyExists
+        );
+        ensure!(
+            !self
+                .data::<Data>()
+                .bundled_tokens
+                .contains(&(contract_address, token_id.clone())),
+            MarketplaceError::TokenAlreadyInBundle
+        );
         self.check_token_owner(contract_address, token_id.clone())?;
+        self.check_marketplace_approval(contract_address, token_id.clone(), Self::env().caller())?;
+        if escrow {
+            self.transfer_escrowed_token(
+                contract_address,
+                token_id.clone(),
+                Self::env().account_id(),
+            )?;
+        }
         self.data::<Data>().items.insert(
             &(contract_address, token_id.clone()),
             &Item {
                 owner: Self::env().caller(),
                 price,
+                dutch_auction: None,
+                starts_at,
+                expires_at,
+                currency,
+                escrowed: escrow,
+                reserved_for,
+            },
+        );
+        self.emit_token_listed_event(
+            contract_address,
+            token_id,
+            Some(price),
+            currency,
+            reserved_for,
+        );
+        Ok(())
+    }
+
+    /// Creates a NFT item sale with a price declining from `start_price`
+    /// to `end_price` between `start_time` and `end_time`.
+    default fn list_dutch(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        start_price: Balance,
+        end_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        currency: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.check_currency(currency)?;
+        ensure!(
+            start_price >= end_price && start_time < end_time,
+            MarketplaceError::InvalidDutchAuction
+        );
+        ensure!(
+            !self.is_token_listed(contract_address, token_id.clone()),
+            MarketplaceError::ItemAlreadyListedForSale
+        );
+        ensure!(
+            !self.is_token_auctioned(contract_address, token_id.clone()),
+            MarketplaceError::AuctionAlreadyExists
+        );
+        ensure!(
+            !self
+                .data::<Data>()
+                .bundled_tokens
+                .contains(&(contract_address, token_id.clone())),
+            MarketplaceError::TokenAlreadyInBundle
+        );
+        self.check_token_owner(contract_address, token_id.clone())?;
+        self.check_marketplace_approval(contract_address, token_id.clone(), Self::env().caller())?;
+        self.data::<Data>().items.insert(
+            &(contract_address, token_id.clone()),
+            &Item {
+                owner: Self::env().caller(),
+                price: start_price,
+                dutch_auction: Some(DutchAuction {
+                    end_price,
+                    start_time,
+                    end_time,
+                }),
+                starts_at: None,
+                expires_at: None,
+                currency,
+                escrowed: false,
+                reserved_for: None,
             },
         );
-        self.emit_token_listed_event(contract_address, token_id, Some(price));
+        self.emit_dutch_auction_listed_event(
+            contract_address,
+            token_id,
+            start_price,
+            end_price,
+            start_time,
+            end_time,
+        );
         Ok(())
     }
 
-    /// Removes a NFT from the marketplace sale.
+    /// Removes a NFT from the marketplace sale. A bundle containing the NFT is removed as well.
+    /// Escrowed token is returned to the seller.
     default fn unlist(
         &mut self,
         contract_address: AccountId,
         token_id: Id,
     ) -> Result<(), MarketplaceError> {
+        // Expired listings can be removed as well.
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_id.clone()));
+        if let Some(Item {
+            owner,
+            escrowed: true,
+            ..
+        }) = item
+        {
+            let caller = Self::env().caller();
+            ensure!(owner == caller, MarketplaceError::NotOwner);
+            // Item is kept if the token can not be returned, so it can be unlisted again.
+            self.transfer_escrowed_token(contract_address, token_id.clone(), caller)?;
+            self.data::<Data>()
+                .items
+                .remove(&(contract_address, token_id.clone()));
+            self.emit_token_listed_event(contract_address, token_id, None, None, None);
+            return Ok(())
+        }
+
+        let bundle_id = self
+            .data::<Data>()
+            .bundled_tokens
+            .get(&(contract_address, token_id.clone()));
         ensure!(
-            self.is_token_listed(contract_address, token_id.clone()),
+            item.is_some() || bundle_id.is_some(),
             MarketplaceError::ItemNotListedForSale
         );
         self.check_token_owner(contract_address, token_id.clone())?;
 
-        self.data::<Data>()
-            .items
-            .remove(&(contract_address, token_id.clone()));
-        self.emit_token_listed_event(contract_address, token_id, None);
+        if let Some(bundle_id) = bundle_id {
+            self.remove_bundle(bundle_id);
+            self.emit_bundle_unlisted_event(bundle_id);
+        }
+        if item.is_some() {
+            self.data::<Data>()
+                .items
+                .remove(&(contract_address, token_id.clone()));
+            self.emit_token_listed_event(contract_address, token_id, None, None, None);
+        }
         Ok(())
     }
 
     /// Buys NFT item from the marketplace.
+    /// Value transferred above the price is credited to the buyer's pending balance.
     #[modifiers(non_reentrant)]
     default fn buy(
         &mut self,
         contract_address: AccountId,
         token_id: Id,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.buy_token(contract_address, token_id, None, referrer)
+    }
+
+    /// Buys NFT item from the marketplace if its current price does not exceed `max_price`.
+    #[modifiers(non_reentrant)]
+    default fn buy_with_max_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        max_price: Balance,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.buy_token(contract_address, token_id, Some(max_price), referrer)
+    }
+
+    /// Updates price of a listed NFT item.
+    default fn update_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        new_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.is_token_listed(contract_address, token_id.clone()),
+            MarketplaceError::ItemNotListedForSale
+        );
+        let mut item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_id.clone()))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            item.owner == Self::env().caller(),
+            MarketplaceError::NotOwner
+        );
+        if let Some(auction) = &item.dutch_auction {
+            ensure!(
+                new_price >= auction.end_price,
+                MarketplaceError::InvalidDutchAuction
+            );
+        }
+
+        let old_price = item.price;
+        item.price = new_price;
+        self.data::<Data>()
+            .items
+            .insert(&(contract_address, token_id.clone()), &item);
+        self.emit_listing_price_updated_event(contract_address, token_id, old_price, new_price);
+        Ok(())
+    }
+
+    /// Removes a listing whose token is no longer owned by the lister. Can be called by anyone.
+    default fn prune_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
     ) -> Result<(), MarketplaceError> {
         let item = self
             .data::<Data>()
             .items
             .get(&(contract_address, token_id.clone()))
             .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        ensure!(
+            self.check_listing_owner(contract_address, token_id.clone(), &item)
+                .is_err(),
+            MarketplaceError::ListingStillValid
+        );
 
-        let token_owner = PSP34Ref::owner_of(&contract_address, token_id.clone())
-            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        self.data::<Data>()
+            .items
+            .remove(&(contract_address, token_id.clone()));
+        self.emit_listing_invalidated_event(contract_address, token_id, item.owner);
+        Ok(())
+    }
+
+    /// Creates sales of several NFT items on the marketplace.
+    default fn list_batch(
+        &mut self,
+        items: Vec<(AccountId, Id, Balance)>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<Result<(), MarketplaceError>>, MarketplaceError> {
+        let mut results = Vec::with_capacity(items.len());
+        for (contract_address, token_id, price) in items {
+            match self.list(contract_address, token_id, price) {
+                Err(error) if all_or_nothing => return Err(error),
+                result => results.push(result),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Removes several NFTs from the marketplace sale.
+    default fn unlist_batch(
+        &mut self,
+        items: Vec<(AccountId, Id)>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<Result<(), MarketplaceError>>, MarketplaceError> {
+        let mut results = Vec::with_capacity(items.len());
+        for (contract_address, token_id) in items {
+            match self.unlist(contract_address, token_id) {
+                Err(error) if all_or_nothing => return Err(error),
+                result => results.push(result),
+            }
+        }
+        Ok(results)
+    }
+
+    /// Buys several NFT items from the marketplace. Each item is charged its current price,
+    /// transferred value must cover the sum of prices and any surplus is credited to the buyer's
+    /// pending balance.
+    #[modifiers(non_reentrant)]
+    default fn buy_batch(
+        &mut self,
+        items: Vec<(AccountId, Id)>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<Result<(), MarketplaceError>>, MarketplaceError> {
         let caller = Self::env().caller();
-        ensure!(token_owner != caller, MarketplaceError::AlreadyOwner);
+        let mut results = Vec::with_capacity(items.len());
+        let mut sales = Vec::new();
+        let mut total_price: Balance = 0;
 
-        let value = Self::env().transferred_value();
-        self.check_price(value, item.price)?;
+        for (contract_address, token_id) in items {
+            match self.check_purchase(contract_address, token_id.clone(), caller, None) {
+                Ok((item, token_owner, price)) => {
+                    // Only native prices are covered by the transferred value.
+                    if item.currency.is_none() {
+                        total_price = total_price
+                            .checked_add(price)
+                            .ok_or(MarketplaceError::BadBuyValue)?;
+                    }
+                    sales.push((
+                        results.len(),
+                        contract_address,
+                        token_id,
+                        token_owner,
+                        price,
+                        item.currency,
+                    ));
+                    results.push(Ok(()));
+                }
+                Err(error) if all_or_nothing => return Err(error),
+                Err(error) => results.push(Err(error)),
+            }
+        }
 
-        let collection = self
-            .data::<Data>()
-            .registered_collections
-            .get(&contract_address)
-            .ok_or(MarketplaceError::NotRegisteredContract)?;
+        let value = Self::env().transferred_value();
+        self.check_price(value, total_price)?;
 
-        let marketplace_fee = value
-            .checked_mul(self.data::<Data>().fee as u128)
-            .unwrap_or_default()
-            / 10_000;
-        let author_royalty = value
-            .checked_mul(collection.royalty as u128)
-            .unwrap_or_default()
-            / 10_000;
-        let seller_fee = value
-            .checked_sub(marketplace_fee)
-            .unwrap_or_default()
-            .checked_sub(author_royalty)
-            .unwrap_or_default();
+        let mut spent: Balance = 0;
+        for (index, contract_address, token_id, token_owner, price, currency) in sales {
+            match self.sell_and_delist_token(
+                contract_address,
+                token_id,
+                token_owner,
+                caller,
+                price,
+                currency,
+                None,
+            ) {
+                Ok(()) if currency.is_none() => spent += price,
+                Ok(()) => (),
+                // Token was not transferred, so no funds were paid out for it.
+                Err(MarketplaceError::UnableToTransferToken) if !all_or_nothing => {
+                    results[index] = Err(MarketplaceError::UnableToTransferToken)
+                }
+                Err(error) => return Err(error),
+            }
+        }
 
-        self.transfer_token(
-            contract_address,
-            token_id,
-            token_owner,
-            caller,
-            seller_fee,
-            marketplace_fee,
-            collection.royalty_receiver,
-            author_royalty,
-            value,
-        )
+        self.add_pending_balance(caller, value - spent);
+        Ok(results)
     }
 
     /// Registers NFT collection to the marketplace.
     default fn register(
         &mut self,
         contract_address: AccountId,
-        royalty_receiver: AccountId,
+        royalty_receivers: Vec<(AccountId, u16)>,
         royalty: u16,
         marketplace_ipfs: String,
     ) -> Result<(), MarketplaceError> {
         let max_fee = self.data::<Data>().max_fee;
         self.check_fee(royalty, max_fee)?;
+        self.check_royalty_receivers(&royalty_receivers)?;
 
         let caller = Self::env().caller();
 
@@ -328,7 +877,7 @@ where
             self.data::<Data>().registered_collections.insert(
                 &contract_address,
                 &RegisteredCollection {
-                    royalty_receiver,
+                    royalty_receivers,
                     royalty,
                     marketplace_ipfs,
                 },
@@ -348,34 +897,368 @@ where
             .get(&contract_address)
     }
 
-    /// Sets the marketplace fee.
-    #[modifiers(only_owner)]
-    default fn set_marketplace_fee(&mut self, fee: u16) -> Result<(), MarketplaceError> {
-        let max_fee = self.data::<Data>().max_fee;
-        self.check_fee(fee, max_fee)?;
-        self.data::<Data>().fee = fee;
+    /// Sets royalty of a single token overriding the collection royalty.
+    /// Passing `None` removes the override.
+    default fn set_token_royalty(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        royalty: Option<u16>,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>()
+                .registered_collections
+                .contains(&contract_address),
+            MarketplaceError::NotRegisteredContract
+        );
+        if let Some(royalty) = royalty {
+            let max_fee = self.data::<Data>().max_fee;
+            self.check_fee(royalty, max_fee)?;
+        }
+
+        let caller = Self::env().caller();
+
+        // Check if caller is Marketplace owner or NFT owner.
+        if self.data::<ownable::Data>().owner != caller
+            && OwnableRef::owner(&contract_address) != caller
+        {
+            return Err(MarketplaceError::NotOwner)
+        }
+
+        let key = (contract_address, token_id.clone());
+        match royalty {
+            Some(royalty) => {
+                self.data::<Data>().token_royalties.insert(&key, &royalty);
+            }
+            None => self.data::<Data>().token_royalties.remove(&key),
+        }
+        self.emit_token_royalty_set_event(contract_address, token_id, royalty);
+        Ok(())
+    }
+
+    /// Gets royalty charged on sale of the token.
+    default fn get_effective_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Option<u16> {
+        let collection = self
+            .data::<Data>()
+            .registered_collections
+            .get(&contract_address)?;
+        Some(self.token_royalty(contract_address, token_id, &collection))
+    }
+
+    /// Proposes a new marketplace fee which can be set once the fee change delay passes.
+    /// Fee decreases are applied immediately.
+    #[modifiers(only_owner)]
+    default fn propose_marketplace_fee(&mut self, fee: u16) -> Result<(), MarketplaceError> {
+        let max_fee = self.data::<Data>().max_fee;
+        self.check_fee(fee, max_fee)?;
+
+        // Sellers can't be surprised by a lower fee so it doesn't need to wait.
+        // Pending increase is dropped so it can't override the lower fee later.
+        if fee <= self.data::<Data>().fee {
+            if let Some((pending_fee, _)) = self.data::<Data>().pending_fee.take() {
+                self.emit_fee_change_cancelled_event(Some(pending_fee), None);
+            }
+            self.data::<Data>().fee = fee;
+            self.emit_fee_change_executed_event(Some(fee), None);
+            return Ok(())
+        }
+
+        let executable_at = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().fee_change_delay);
+        self.data::<Data>().pending_fee = Some((fee, executable_at));
+        self.emit_fee_change_proposed_event(Some(fee), None, executable_at);
+
+        Ok(())
+    }
+
+    /// Sets the proposed marketplace fee after the fee change delay.
+    #[modifiers(only_owner)]
+    default fn execute_marketplace_fee(&mut self) -> Result<(), MarketplaceError> {
+        let (fee, executable_at) = self
+            .data::<Data>()
+            .pending_fee
+            .ok_or(MarketplaceError::NoPendingFeeChange)?;
+        ensure!(
+            Self::env().block_timestamp() >= executable_at,
+            MarketplaceError::FeeChangeNotReady
+        );
+
+        self.data::<Data>().pending_fee = None;
+        self.data::<Data>().fee = fee;
+        self.emit_fee_change_executed_event(Some(fee), None);
 
         Ok(())
     }
 
+    /// Cancels the proposed marketplace fee.
+    #[modifiers(only_owner)]
+    default fn cancel_marketplace_fee(&mut self) -> Result<(), MarketplaceError> {
+        let (fee, _) = self
+            .data::<Data>()
+            .pending_fee
+            .take()
+            .ok_or(MarketplaceError::NoPendingFeeChange)?;
+        self.emit_fee_change_cancelled_event(Some(fee), None);
+
+        Ok(())
+    }
+
+    /// Gets the proposed marketplace fee and the time it can be set at.
+    default fn get_pending_marketplace_fee(&self) -> Option<(u16, Timestamp)> {
+        self.data::<Data>().pending_fee
+    }
+
+    /// Sets the delay of marketplace fee and fee recipient changes. The delay can only be increased.
+    #[modifiers(only_owner)]
+    default fn set_fee_change_delay(&mut self, delay: Timestamp) -> Result<(), MarketplaceError> {
+        ensure!(
+            delay >= self.data::<Data>().fee_change_delay && delay <= MAX_FEE_CHANGE_DELAY,
+            MarketplaceError::InvalidFeeChangeDelay
+        );
+        self.data::<Data>().fee_change_delay = delay;
+
+        Ok(())
+    }
+
+    /// Gets the delay of marketplace fee and fee recipient changes.
+    default fn get_fee_change_delay(&self) -> Timestamp {
+        self.data::<Data>().fee_change_delay
+    }
+
     /// Gets the marketplace fee.
     default fn get_marketplace_fee(&self) -> u16 {
         self.data::<Data>().fee
     }
 
+    /// Sets the share of the marketplace fee paid to referrers, in basis points.
+    #[modifiers(only_owner)]
+    default fn set_referral_share(&mut self, share: u16) -> Result<(), MarketplaceError> {
+        self.check_fee(share, 10_000)?;
+        self.data::<Data>().referral_share = share;
+
+        Ok(())
+    }
+
+    /// Gets the share of the marketplace fee paid to referrers, in basis points.
+    default fn get_referral_share(&self) -> u16 {
+        self.data::<Data>().referral_share
+    }
+
+    /// Gets total referral fees earned by the referrer in the given currency.
+    default fn get_referral_earnings(
+        &self,
+        referrer: AccountId,
+        currency: Option<AccountId>,
+    ) -> Balance {
+        self.data::<Data>()
+            .referral_earnings
+            .get(&(referrer, currency))
+            .unwrap_or_default()
+    }
+
+    /// Sets PSP34 collection whose holders get a marketplace fee discount.
+    #[modifiers(only_owner)]
+    default fn set_membership_collection(
+        &mut self,
+        contract_address: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>().membership_collection = contract_address;
+
+        Ok(())
+    }
+
+    /// Gets PSP34 collection whose holders get a marketplace fee discount.
+    default fn get_membership_collection(&self) -> Option<AccountId> {
+        self.data::<Data>().membership_collection
+    }
+
+    /// Sets fee discount tiers as `(min tokens held, discount in basis points)`
+    /// ordered by the number of tokens.
+    #[modifiers(only_owner)]
+    default fn set_discount_tiers(
+        &mut self,
+        tiers: Vec<(u32, u16)>,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            tiers.len() <= MAX_DISCOUNT_TIERS
+                && tiers.iter().all(|(_, discount)| *discount <= 10_000)
+                && tiers.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            MarketplaceError::InvalidDiscountTiers
+        );
+        self.data::<Data>().discount_tiers = tiers;
+
+        Ok(())
+    }
+
+    /// Gets fee discount tiers.
+    default fn get_discount_tiers(&self) -> Vec<(u32, u16)> {
+        self.data::<Data>().discount_tiers.clone()
+    }
+
+    /// Gets marketplace fee discount of the account in basis points.
+    default fn get_fee_discount(&self, account: AccountId) -> u16 {
+        self.fee_discount(account)
+    }
+
+    /// Proposes the marketplace fee charged on sales of the collection instead of the global fee,
+    /// which can be set once the fee change delay passes. Passing `None` removes the override.
+    /// Changes which don't increase the collection fee are applied immediately.
+    #[modifiers(only_owner)]
+    default fn propose_collection_fee(
+        &mut self,
+        contract_address: AccountId,
+        fee: Option<u16>,
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            self.data::<Data>()
+                .registered_collections
+                .contains(&contract_address),
+            MarketplaceError::NotRegisteredContract
+        );
+        if let Some(fee) = fee {
+            let max_fee = self.data::<Data>().max_fee;
+            self.check_fee(fee, max_fee)?;
+        }
+
+        // Removed override falls back to the global fee, which may be higher.
+        let new_fee = fee.unwrap_or(self.data::<Data>().fee);
+        if new_fee <= self.collection_fee(contract_address) {
+            if let Some((pending_fee, _)) = self
+                .data::<Data>()
+                .pending_collection_fees
+                .get(&contract_address)
+            {
+                self.data::<Data>()
+                    .pending_collection_fees
+                    .remove(&contract_address);
+                self.emit_collection_fee_change_cancelled_event(contract_address, pending_fee);
+            }
+            self.update_collection_fee(contract_address, fee);
+            return Ok(())
+        }
+
+        let executable_at = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().fee_change_delay);
+        self.data::<Data>()
+            .pending_collection_fees
+            .insert(&contract_address, &(fee, executable_at));
+        self.emit_collection_fee_change_proposed_event(contract_address, fee, executable_at);
+
+        Ok(())
+    }
+
+    /// Sets the proposed collection fee after the fee change delay.
+    #[modifiers(only_owner)]
+    default fn execute_collection_fee(
+        &mut self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let (fee, executable_at) = self
+            .data::<Data>()
+            .pending_collection_fees
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NoPendingFeeChange)?;
+        ensure!(
+            Self::env().block_timestamp() >= executable_at,
+            MarketplaceError::FeeChangeNotReady
+        );
+
+        self.data::<Data>()
+            .pending_collection_fees
+            .remove(&contract_address);
+        self.update_collection_fee(contract_address, fee);
+
+        Ok(())
+    }
+
+    /// Cancels the proposed collection fee.
+    #[modifiers(only_owner)]
+    default fn cancel_collection_fee(
+        &mut self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let (fee, _) = self
+            .data::<Data>()
+            .pending_collection_fees
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NoPendingFeeChange)?;
+        self.data::<Data>()
+            .pending_collection_fees
+            .remove(&contract_address);
+        self.emit_collection_fee_change_cancelled_event(contract_address, fee);
+
+        Ok(())
+    }
+
+    /// Gets the proposed collection fee and the time it can be set at.
+    default fn get_pending_collection_fee(
+        &self,
+        contract_address: AccountId,
+    ) -> Option<(Option<u16>, Timestamp)> {
+        self.data::<Data>()
+            .pending_collection_fees
+            .get(&contract_address)
+    }
+
+    /// Gets the marketplace fee charged on sales of the collection.
+    default fn get_effective_fee(&self, contract_address: AccountId) -> u16 {
+        self.collection_fee(contract_address)
+    }
+
     /// Gets max fee that can be applied to an item price.
     default fn get_max_fee(&self) -> u16 {
         self.data::<Data>().max_fee
     }
 
-    /// Checks if NFT token is listed on the marketplace and returns token price.
+    /// Checks if NFT token is listed on the marketplace and returns current token price.
+    /// Returns `None` if the sale has not started yet or has expired.
     default fn get_price(&self, contract_address: AccountId, token_id: Id) -> Option<Balance> {
         match self.data::<Data>().items.get(&(contract_address, token_id)) {
-            Some(item) => Some(item.price),
+            Some(item) if self.check_listing_active(&item).is_ok() => Some(self.item_price(&item)),
             _ => None,
         }
     }
 
+    /// Gets NFT item listing.
+    default fn get_listing(&self, contract_address: AccountId, token_id: Id) -> Option<Item> {
+        self.data::<Data>().items.get(&(contract_address, token_id))
+    }
+
+    /// Checks if listed NFT item can currently be bought.
+    default fn is_listing_fillable(&self, contract_address: AccountId, token_id: Id) -> bool {
+        let item = match self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_id.clone()))
+        {
+            Some(item) => item,
+            None => return false,
+        };
+        if self.check_listing_active(&item).is_err()
+            || self.check_currency(item.currency).is_err()
+            || !self
+                .data::<Data>()
+                .registered_collections
+                .contains(&contract_address)
+            || self
+                .check_listing_owner(contract_address, token_id.clone(), &item)
+                .is_err()
+        {
+            return false
+        }
+        // Escrowed token is transferred by the marketplace as its owner.
+        item.escrowed
+            || self
+                .check_marketplace_approval(contract_address, token_id, item.owner)
+                .is_ok()
+    }
+
     /// Sets contract metadata (ipfs url)
     #[modifiers(only_owner)]
     default fn set_contract_metadata(
@@ -392,7 +1275,7 @@ where
         self.data::<Data>().registered_collections.insert(
             &contract_address,
             &RegisteredCollection {
-                royalty_receiver: collection.royalty_receiver,
+                royalty_receivers: collection.royalty_receivers,
                 marketplace_ipfs: ipfs,
                 royalty: collection.royalty,
             },
@@ -406,16 +1289,120 @@ where
         self.data::<Data>().market_fee_recipient.unwrap()
     }
 
-    /// Sets the marketplace fee recipient.
+    /// Proposes a new marketplace fee recipient which can be set once the fee change delay passes.
     #[modifiers(only_owner)]
-    default fn set_fee_recipient(
+    default fn propose_fee_recipient(
         &mut self,
         fee_recipient: AccountId,
     ) -> Result<(), MarketplaceError> {
+        let executable_at = Self::env()
+            .block_timestamp()
+            .saturating_add(self.data::<Data>().fee_change_delay);
+        self.data::<Data>().pending_fee_recipient = Some((fee_recipient, executable_at));
+        self.emit_fee_change_proposed_event(None, Some(fee_recipient), executable_at);
+
+        Ok(())
+    }
+
+    /// Sets the proposed marketplace fee recipient after the fee change delay.
+    #[modifiers(only_owner)]
+    default fn execute_fee_recipient(&mut self) -> Result<(), MarketplaceError> {
+        let (fee_recipient, executable_at) = self
+            .data::<Data>()
+            .pending_fee_recipient
+            .ok_or(MarketplaceError::NoPendingFeeChange)?;
+        ensure!(
+            Self::env().block_timestamp() >= executable_at,
+            MarketplaceError::FeeChangeNotReady
+        );
+
+        self.data::<Data>().pending_fee_recipient = None;
         self.data::<Data>().market_fee_recipient = Option::Some(fee_recipient);
+        self.emit_fee_change_executed_event(None, Some(fee_recipient));
+
+        Ok(())
+    }
+
+    /// Cancels the proposed marketplace fee recipient.
+    #[modifiers(only_owner)]
+    default fn cancel_fee_recipient(&mut self) -> Result<(), MarketplaceError> {
+        let (fee_recipient, _) = self
+            .data::<Data>()
+            .pending_fee_recipient
+            .take()
+            .ok_or(MarketplaceError::NoPendingFeeChange)?;
+        self.emit_fee_change_cancelled_event(None, Some(fee_recipient));
+
+        Ok(())
+    }
+
+    /// Gets the proposed marketplace fee recipient and the time it can be set at.
+    default fn get_pending_fee_recipient(&self) -> Option<(AccountId, Timestamp)> {
+        self.data::<Data>().pending_fee_recipient
+    }
+
+    /// Allows PSP22 token to be used as a listing currency.
+    #[modifiers(only_owner)]
+    default fn add_currency(&mut self, currency: AccountId) -> Result<(), MarketplaceError> {
+        ensure!(
+            !self.data::<Data>().allowed_currencies.contains(&currency),
+            MarketplaceError::CurrencyAlreadyAllowed
+        );
+        self.data::<Data>().allowed_currencies.push(currency);
 
         Ok(())
     }
+
+    /// Disallows PSP22 token as a listing currency. Existing listings in this currency
+    /// can not be bought until the token is allowed again.
+    #[modifiers(only_owner)]
+    default fn remove_currency(&mut self, currency: AccountId) -> Result<(), MarketplaceError> {
+        let currencies = &mut self.data::<Data>().allowed_currencies;
+        let index = currencies
+            .iter()
+            .position(|allowed| *allowed == currency)
+            .ok_or(MarketplaceError::CurrencyNotAllowed)?;
+        currencies.remove(index);
+
+        Ok(())
+    }
+
+    /// Gets PSP22 tokens allowed as listing currencies.
+    default fn get_currencies(&self) -> Vec<AccountId> {
+        self.data::<Data>().allowed_currencies.clone()
+    }
+
+    /// Withdraws caller's pending proceeds to the caller.
+    default fn withdraw(&mut self) -> Result<Balance, MarketplaceError> {
+        self.withdraw_to(Self::env().caller())
+    }
+
+    /// Withdraws caller's pending proceeds to the `to` account.
+    #[modifiers(non_reentrant)]
+    default fn withdraw_to(&mut self, to: AccountId) -> Result<Balance, MarketplaceError> {
+        let caller = Self::env().caller();
+        let amount = self
+            .data::<Data>()
+            .pending_balances
+            .get(&caller)
+            .unwrap_or_default();
+        ensure!(amount > 0, MarketplaceError::NothingToWithdraw);
+
+        self.data::<Data>().pending_balances.remove(&caller);
+        Self::env()
+            .transfer(to, amount)
+            .map_err(|_| MarketplaceError::WithdrawFailed)?;
+        self.emit_proceeds_withdrawn_event(caller, to, amount);
+        Ok(amount)
+    }
+
+    /// Gets proceeds and refunds of the account waiting to be withdrawn.
+    default fn pending_balance(&self, account: AccountId) -> Balance {
+        self.data::<Data>()
+            .pending_balances
+            .get(&account)
+            .unwrap_or_default()
+    }
 }
 
 impl<T> MarketplaceSaleEvents for T
@@ -427,6 +1414,8 @@ where
         _contract: AccountId,
         _token_id: Id,
         _price: Option<Balance>,
+        _currency: Option<AccountId>,
+        _reserved_for: Option<AccountId>,
     ) {
     }
 
@@ -435,10 +1424,94 @@ where
         _contract: AccountId,
         _token_id: Id,
         _price: Balance,
+        _currency: Option<AccountId>,
+    ) {
+    }
+
+    default fn emit_listing_price_updated_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _old_price: Balance,
+        _new_price: Balance,
+    ) {
+    }
+
+    default fn emit_dutch_auction_listed_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _start_price: Balance,
+        _end_price: Balance,
+        _start_time: Timestamp,
+        _end_time: Timestamp,
     ) {
     }
 
     default fn emit_collection_registered_event(&self, _contract: AccountId) {}
+
+    default fn emit_token_royalty_set_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _royalty: Option<u16>,
+    ) {
+    }
+
+    default fn emit_collection_fee_set_event(&self, _contract: AccountId, _fee: Option<u16>) {}
+
+    default fn emit_collection_fee_change_proposed_event(
+        &self,
+        _contract: AccountId,
+        _fee: Option<u16>,
+        _executable_at: Timestamp,
+    ) {
+    }
+
+    default fn emit_collection_fee_change_cancelled_event(
+        &self,
+        _contract: AccountId,
+        _fee: Option<u16>,
+    ) {
+    }
+
+    default fn emit_fee_change_proposed_event(
+        &self,
+        _fee: Option<u16>,
+        _fee_recipient: Option<AccountId>,
+        _executable_at: Timestamp,
+    ) {
+    }
+
+    default fn emit_fee_change_executed_event(
+        &self,
+        _fee: Option<u16>,
+        _fee_recipient: Option<AccountId>,
+    ) {
+    }
+
+    default fn emit_fee_change_cancelled_event(
+        &self,
+        _fee: Option<u16>,
+        _fee_recipient: Option<AccountId>,
+    ) {
+    }
+
+    default fn emit_listing_invalidated_event(
+        &self,
+        _contract: AccountId,
+        _token_id: Id,
+        _owner: AccountId,
+    ) {
+    }
+
+    default fn emit_proceeds_withdrawn_event(
+        &self,
+        _account: AccountId,
+        _to: AccountId,
+        _amount: Balance,
+    ) {
+    }
 }
 
 impl<T> Internal for T
@@ -468,14 +1541,175 @@ where
         }
     }
 
+    default fn check_listing(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+    ) -> Result<(Item, AccountId), MarketplaceError> {
+        let item = self
+            .data::<Data>()
+            .items
+            .get(&(contract_address, token_id.clone()))
+            .ok_or(MarketplaceError::ItemNotListedForSale)?;
+        self.check_listing_active(&item)?;
+        if let Some(reserved_for) = item.reserved_for {
+            ensure!(reserved_for == buyer, MarketplaceError::ListingReserved);
+        }
+
+        let token_owner = self.check_listing_owner(contract_address, token_id, &item)?;
+        ensure!(token_owner != buyer, MarketplaceError::AlreadyOwner);
+
+        Ok((item, token_owner))
+    }
+
+    default fn check_listing_owner(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        item: &Item,
+    ) -> Result<AccountId, MarketplaceError> {
+        let token_owner = PSP34Ref::owner_of(&contract_address, token_id)
+            .ok_or(MarketplaceError::TokenDoesNotExist)?;
+        // Escrowed token is sold on behalf of the seller.
+        let expected_owner = if item.escrowed {
+            Self::env().account_id()
+        } else {
+            item.owner
+        };
+        ensure!(
+            token_owner == expected_owner,
+            MarketplaceError::ListingOwnerChanged
+        );
+
+        Ok(item.owner)
+    }
+
+    default fn buy_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        max_price: Option<Balance>,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        let (item, token_owner, price) =
+            self.check_purchase(contract_address, token_id.clone(), caller, max_price)?;
+
+        let value = Self::env().transferred_value();
+        let surplus = match item.currency {
+            Some(_) => {
+                // Token priced items are paid by PSP22 transfers from the buyer.
+                ensure!(value == 0, MarketplaceError::BadBuyValue);
+                0
+            }
+            // Only the price is charged, any surplus is returned to the buyer.
+            None => self.check_price(value, price)?,
+        };
+        self.sell_and_delist_token(
+            contract_address,
+            token_id,
+            token_owner,
+            caller,
+            price,
+            item.currency,
+            referrer,
+        )?;
+        self.add_pending_balance(caller, surplus);
+        Ok(())
+    }
+
+    default fn check_purchase(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+        max_price: Option<Balance>,
+    ) -> Result<(Item, AccountId, Balance), MarketplaceError> {
+        let (item, token_owner) = self.check_listing(contract_address, token_id, buyer)?;
+        // Items priced in a currency which is no longer allowed can not be bought.
+        self.check_currency(item.currency)?;
+        let price = self.item_price(&item);
+        if let Some(max_price) = max_price {
+            ensure!(price <= max_price, MarketplaceError::PriceExceedsMaxPrice);
+        }
+
+        Ok((item, token_owner, price))
+    }
+
+    default fn sell_and_delist_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Option<AccountId>,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.sell_token(
+            contract_address,
+            token_id.clone(),
+            token_owner,
+            buyer,
+            price,
+            currency,
+            referrer,
+        )?;
+        self.delist_token(contract_address, token_id);
+        Ok(())
+    }
+
+    default fn delist_token(&mut self, contract_address: AccountId, token_id: Id) {
+        // Sold item is removed, otherwise escrowed item would block the buyer from listing the token.
+        self.data::<Data>()
+            .items
+            .remove(&(contract_address, token_id.clone()));
+        // Bundle can no longer be bought once one of its tokens is sold.
+        if let Some(bundle_id) = self
+            .data::<Data>()
+            .bundled_tokens
+            .get(&(contract_address, token_id))
+        {
+            self.remove_bundle(bundle_id);
+            self.emit_bundle_unlisted_event(bundle_id);
+        }
+    }
+
     default fn check_price(
         &self,
         transferred_value: Balance,
         price: Balance,
-    ) -> Result<(), MarketplaceError> {
-        ensure!(transferred_value >= price, MarketplaceError::BadBuyValue);
+    ) -> Result<Balance, MarketplaceError> {
+        transferred_value
+            .checked_sub(price)
+            .ok_or(MarketplaceError::BadBuyValue)
+    }
 
-        Ok(())
+    default fn item_price(&self, item: &Item) -> Balance {
+        let auction = match &item.dutch_auction {
+            Some(auction) => auction,
+            None => return item.price,
+        };
+
+        let now = Self::env().block_timestamp();
+        if now <= auction.start_time {
+            return item.price
+        }
+        if now >= auction.end_time {
+            return auction.end_price
+        }
+
+        let elapsed = (now - auction.start_time) as u128;
+        let duration = (auction.end_time - auction.start_time) as u128;
+        let discount = item
+            .price
+            .checked_sub(auction.end_price)
+            .unwrap_or_default()
+            .checked_mul(elapsed)
+            .unwrap_or_default()
+            / duration;
+        item.price.checked_sub(discount).unwrap_or_default()
     }
 
     default fn check_fee(&self, fee: u16, max_fee: u16) -> Result<(), MarketplaceError> {
@@ -484,24 +1718,43 @@ where
         Ok(())
     }
 
+    default fn check_listing_active(&self, item: &Item) -> Result<(), MarketplaceError> {
+        let now = Self::env().block_timestamp();
+        if let Some(starts_at) = item.starts_at {
+            ensure!(now >= starts_at, MarketplaceError::ListingNotStarted);
+        }
+        if let Some(expires_at) = item.expires_at {
+            ensure!(now < expires_at, MarketplaceError::ListingExpired);
+        }
+        Ok(())
+    }
+
     default fn is_token_listed(&self, contract_address: AccountId, token_id: Id) -> bool {
-        self.data::<Data>()
-            .items
-            .get(&(contract_address, token_id))
-            .is_some()
+        match self.data::<Data>().items.get(&(contract_address, token_id)) {
+            Some(item) => self.check_listing_active(&item) != Err(MarketplaceError::ListingExpired),
+            None => false,
+        }
+    }
+
+    default fn is_token_auctioned(&self, contract_address: AccountId, token_id: Id) -> bool {
+        let key = (contract_address, token_id);
+        self.data::<Data>().auctions.contains(&key)
+            || self.data::<Data>().sealed_auction_ids.contains(&key)
     }
 
     default fn transfer_token(
-        &self,
+        &mut self,
         contract_address: AccountId,
         token_id: Id,
         token_owner: AccountId,
         buyer: AccountId,
         seller_fee: Balance,
         marketplace_fee: Balance,
-        royalty_receiver: AccountId,
+        referral: Option<(AccountId, Balance)>,
+        royalty_receivers: Vec<(AccountId, u16)>,
         author_royalty: Balance,
         token_price: Balance,
+        currency: Option<AccountId>,
     ) -> Result<(), MarketplaceError> {
         match PSP34Ref::transfer(
             &contract_address,
@@ -510,25 +1763,363 @@ where
             ink::prelude::vec::Vec::new(),
         ) {
             Ok(()) => {
-                Self::env()
-                    .transfer(token_owner, seller_fee)
-                    .map_err(|_| MarketplaceError::TransferToOwnerFailed)?;
-                Self::env()
-                    .transfer(
-                        self.data::<Data>().market_fee_recipient.unwrap(),
-                        marketplace_fee,
-                    )
-                    .map_err(|_| MarketplaceError::TransferToMarketplaceFailed)?;
-                Self::env()
-                    .transfer(royalty_receiver, author_royalty)
-                    .map_err(|_| MarketplaceError::TransferToAuthorFailed)?;
-                self.emit_token_bought_event(contract_address, token_id, token_price);
+                let market_fee_recipient = self.data::<Data>().market_fee_recipient.unwrap();
+                self.transfer_funds(currency, buyer, token_owner, seller_fee)?;
+                self.transfer_funds(currency, buyer, market_fee_recipient, marketplace_fee)?;
+                if let Some((referrer, referral_fee)) = referral {
+                    self.transfer_funds(currency, buyer, referrer, referral_fee)?;
+                    let earnings = self
+                        .data::<Data>()
+                        .referral_earnings
+                        .get(&(referrer, currency))
+                        .unwrap_or_default();
+                    self.data::<Data>().referral_earnings.insert(
+                        &(referrer, currency),
+                        &earnings.saturating_add(referral_fee),
+                    );
+                }
+                for (receiver, royalty) in self.royalty_shares(&royalty_receivers, author_royalty) {
+                    self.transfer_funds(currency, buyer, receiver, royalty)?;
+                }
+                self.emit_token_bought_event(contract_address, token_id, token_price, currency);
                 Ok(())
             }
             Err(_) => Err(MarketplaceError::UnableToTransferToken),
         }
     }
 
+    default fn sell_token(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        token_owner: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        currency: Option<AccountId>,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError> {
+        self.check_referrer(referrer, buyer, token_owner)?;
+        let nft_royalty = if self.has_royalty_override(contract_address, token_id.clone()) {
+            None
+        } else {
+            self.nft_royalty(contract_address, token_id.clone(), price)
+        };
+        // Better of buyer's and seller's membership discounts applies.
+        let fee_discount = self.fee_discount(buyer).max(self.fee_discount(token_owner));
+        let (seller_fee, marketplace_fee, referral, royalty_receivers, author_royalty) = self
+            .split_price(
+                contract_address,
+                token_id.clone(),
+                price,
+                referrer,
+                fee_discount,
+                nft_royalty,
+            )?;
+
+        self.transfer_token(
+            contract_address,
+            token_id,
+            token_owner,
+            buyer,
+            seller_fee,
+            marketplace_fee,
+            referral,
+            royalty_receivers,
+            author_royalty,
+            price,
+            currency,
+        )
+    }
+
+    default fn split_price(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        referrer: Option<AccountId>,
+        fee_discount: u16,
+        nft_royalty: Option<(AccountId, Balance)>,
+    ) -> Result<
+        (
+            Balance,
+            Balance,
+            Option<(AccountId, Balance)>,
+            Vec<(AccountId, u16)>,
+            Balance,
+        ),
+        MarketplaceError,
+    > {
+        let collection = self
+            .data::<Data>()
+            .registered_collections
+            .get(&contract_address)
+            .ok_or(MarketplaceError::NotRegisteredContract)?;
+
+        let mut marketplace_fee = price
+            .checked_mul(self.collection_fee(contract_address) as u128)
+            .unwrap_or_default()
+            / 10_000;
+        marketplace_fee = marketplace_fee
+            .checked_mul(10_000 - fee_discount.min(10_000) as u128)
+            .unwrap_or_default()
+            / 10_000;
+        let referral = referrer.map(|referrer| {
+            let referral_fee = marketplace_fee
+                .checked_mul(self.data::<Data>().referral_share as u128)
+                .unwrap_or_default()
+                / 10_000;
+            (referrer, referral_fee)
+        });
+        if let Some((_, referral_fee)) = referral {
+            marketplace_fee -= referral_fee;
+        }
+        let (royalty_receivers, author_royalty) = match nft_royalty {
+            Some((receiver, royalty))
+                if !self.has_royalty_override(contract_address, token_id.clone()) =>
+            {
+                let max_royalty = price
+                    .checked_mul(self.data::<Data>().max_fee as u128)
+                    .unwrap_or_default()
+                    / 10_000;
+                (
+                    ink::prelude::vec![(receiver, 10_000)],
+                    royalty.min(max_royalty),
+                )
+            }
+            _ => {
+                let royalty = self.token_royalty(contract_address, token_id, &collection);
+                let author_royalty =
+                    price.checked_mul(royalty as u128).unwrap_or_default() / 10_000;
+                (collection.royalty_receivers, author_royalty)
+            }
+        };
+        let referral_fee = referral.map(|(_, fee)| fee).unwrap_or_default();
+        let seller_fee = price
+            .checked_sub(marketplace_fee)
+            .unwrap_or_default()
+            .checked_sub(referral_fee)
+            .unwrap_or_default()
+            .checked_sub(author_royalty)
+            .unwrap_or_default();
+
+        Ok((
+            seller_fee,
+            marketplace_fee,
+            referral,
+            royalty_receivers,
+            author_royalty,
+        ))
+    }
+
+    default fn nft_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Option<(AccountId, Balance)> {
+        match RoyaltyInfoRef::royalty_info_builder(&contract_address, token_id, price).try_invoke()
+        {
+            Ok(Ok(royalty_info)) => Some(royalty_info),
+            _ => None,
+        }
+    }
+
+    default fn collection_fee(&self, contract_address: AccountId) -> u16 {
+        self.data::<Data>()
+            .collection_fees
+            .get(&contract_address)
+            .unwrap_or(self.data::<Data>().fee)
+    }
+
+    default fn fee_discount(&self, account: AccountId) -> u16 {
+        match self.data::<Data>().membership_collection {
+            Some(membership_collection) if !self.data::<Data>().discount_tiers.is_empty() => {
+                self.tier_discount(PSP34Ref::balance_of(&membership_collection, account))
+            }
+            _ => 0,
+        }
+    }
+
+    default fn tier_discount(&self, tokens: u32) -> u16 {
+        self.data::<Data>()
+            .discount_tiers
+            .iter()
+            .rev()
+            .find(|(min_tokens, _)| tokens >= *min_tokens)
+            .map(|(_, discount)| *discount)
+            .unwrap_or_default()
+    }
+
+    default fn check_referrer(
+        &self,
+        referrer: Option<AccountId>,
+        buyer: AccountId,
+        seller: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        if let Some(referrer) = referrer {
+            ensure!(
+                referrer != buyer && referrer != seller,
+                MarketplaceError::InvalidReferrer
+            );
+        }
+        Ok(())
+    }
+
+    default fn has_royalty_override(&self, contract_address: AccountId, token_id: Id) -> bool {
+        self.data::<Data>()
+            .token_royalties
+            .contains(&(contract_address, token_id))
+            || self
+                .data::<Data>()
+                .registered_collections
+                .get(&contract_address)
+                .map_or(false, |collection| collection.royalty_receivers.len() > 1)
+    }
+
+    default fn token_royalty(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        collection: &RegisteredCollection,
+    ) -> u16 {
+        self.data::<Data>()
+            .token_royalties
+            .get(&(contract_address, token_id))
+            .unwrap_or(collection.royalty)
+    }
+
+    default fn royalty_shares(
+        &self,
+        royalty_receivers: &[(AccountId, u16)],
+        royalty: Balance,
+    ) -> Vec<(AccountId, Balance)> {
+        let mut shares: Vec<(AccountId, Balance)> = royalty_receivers
+            .iter()
+            .map(|(receiver, share)| {
+                (
+                    *receiver,
+                    royalty.checked_mul(*share as u128).unwrap_or_default() / 10_000,
+                )
+            })
+            .collect();
+        let distributed: Balance = shares.iter().map(|(_, amount)| amount).sum();
+        if let Some((_, first)) = shares.first_mut() {
+            *first += royalty.saturating_sub(distributed);
+        }
+        shares
+    }
+
+    default fn check_royalty_receivers(
+        &self,
+        royalty_receivers: &[(AccountId, u16)],
+    ) -> Result<(), MarketplaceError> {
+        let total: u32 = royalty_receivers
+            .iter()
+            .map(|(_, share)| *share as u32)
+            .sum();
+        ensure!(
+            !royalty_receivers.is_empty()
+                && royalty_receivers.len() <= MAX_ROYALTY_RECEIVERS
+                && total == 10_000,
+            MarketplaceError::InvalidRoyaltySplit
+        );
+        Ok(())
+    }
+
+    default fn transfer_funds(
+        &mut self,
+        currency: Option<AccountId>,
+        buyer: AccountId,
+        to: AccountId,
+        amount: Balance,
+    ) -> Result<(), MarketplaceError> {
+        match currency {
+            Some(currency) => {
+                if amount == 0 {
+                    return Ok(())
+                }
+                PSP22Ref::transfer_from(&currency, buyer, to, amount, Vec::new())
+                    .map_err(|_| MarketplaceError::TransferFromBuyerFailed)
+            }
+            None => {
+                self.add_pending_balance(to, amount);
+                Ok(())
+            }
+        }
+    }
+
+    default fn update_collection_fee(&mut self, contract_address: AccountId, fee: Option<u16>) {
+        match fee {
+            Some(fee) => {
+                self.data::<Data>()
+                    .collection_fees
+                    .insert(&contract_address, &fee);
+            }
+            None => {
+                self.data::<Data>()
+                    .collection_fees
+                    .remove(&contract_address)
+            }
+        }
+        self.emit_collection_fee_set_event(contract_address, fee);
+    }
+
+    default fn add_pending_balance(&mut self, account: AccountId, amount: Balance) {
+        if amount == 0 {
+            return
+        }
+        let balance = self
+            .data::<Data>()
+            .pending_balances
+            .get(&account)
+            .unwrap_or_default();
+        self.data::<Data>()
+            .pending_balances
+            .insert(&account, &balance.saturating_add(amount));
+    }
+
+    default fn check_marketplace_approval(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        owner: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        let marketplace = Self::env().account_id();
+        ensure!(
+            PSP34Ref::allowance(&contract_address, owner, marketplace, Some(token_id))
+                || PSP34Ref::allowance(&contract_address, owner, marketplace, None),
+            MarketplaceError::MarketplaceNotApproved
+        );
+
+        Ok(())
+    }
+
+    default fn transfer_escrowed_token(
+        &self,
+        contract_address: AccountId,
+        token_id: Id,
+        to: AccountId,
+    ) -> Result<(), MarketplaceError> {
+        // Transfer into the marketplace calls back its `before_received` message.
+        match PSP34Ref::transfer_builder(&contract_address, to, token_id, Vec::new())
+            .call_flags(CallFlags::default().set_allow_reentry(true))
+            .try_invoke()
+        {
+            Ok(Ok(Ok(()))) => Ok(()),
+            _ => Err(MarketplaceError::UnableToTransferToken),
+        }
+    }
+
+    default fn check_currency(&self, currency: Option<AccountId>) -> Result<(), MarketplaceError> {
+        if let Some(currency) = currency {
+            ensure!(
+                self.data::<Data>().allowed_currencies.contains(&currency),
+                MarketplaceError::CurrencyNotAllowed
+            );
+        }
+        Ok(())
+    }
+
     default fn get_nft_contract_hash(
         &self,
         contract_type: &NftContractType,
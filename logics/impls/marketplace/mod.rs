@@ -1,2 +1,6 @@
+pub mod auction;
+pub mod bundle;
 pub mod marketplace_sale;
+pub mod offer;
+pub mod swap;
 pub mod types;
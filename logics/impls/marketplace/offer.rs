@@ -0,0 +1,665 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::marketplace_sale::Internal;
+use crate::{
+    ensure,
+    impls::marketplace::types::{
+        CollectionOffer,
+        Data,
+        MarketplaceError,
+        Offer,
+        MAX_COLLECTION_OFFERS,
+        MAX_OFFERS_PER_TOKEN,
+    },
+    traits::offer::MarketplaceOffer,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{
+        ownable::*,
+        psp34::{
+            extensions::metadata::PSP34MetadataRef,
+            *,
+        },
+        reentrancy_guard::*,
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+        String,
+        Timestamp,
+    },
+};
+
+pub trait OfferInternal {
+    /// Removes offer from the storage.
+    fn remove_offer(&mut self, offer_id: u64, offer: &Offer);
+
+    /// Makes room for a new offer if the token has max number of offers, by removing
+    /// an expired offer or the lowest offer if it is lower than `price`. Removed offer amount
+    /// is credited to the offer maker's pending balance.
+    fn make_room_for_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Removes collection offer from the storage.
+    fn remove_collection_offer(&mut self, offer_id: u64, contract_address: AccountId);
+
+    /// Removes expired offers of a collection and, if the collection still has max number
+    /// of offers, the lowest offer if it is lower than `price`. Unfilled amount of removed
+    /// offers is credited to the offer makers' pending balances.
+    fn make_room_for_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Stores a collection offer with optional attribute constraint.
+    fn add_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+        attribute: Option<(String, String)>,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Sells caller's NFT to a collection offer, decreasing its remaining quantity.
+    fn fill_collection_offer(
+        &mut self,
+        offer_id: u64,
+        offer: CollectionOffer,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets id of the highest active collection offer without attribute constraint.
+    fn best_collection_offer(&self, contract_address: AccountId) -> Option<u64>;
+}
+
+pub trait OfferEvents {
+    fn emit_offer_made_event(
+        &self,
+        offer_id: u64,
+        contract: AccountId,
+        token_id: Id,
+        buyer: AccountId,
+        price: Balance,
+        expiry: Timestamp,
+    );
+    fn emit_offer_cancelled_event(&self, offer_id: u64);
+    fn emit_offer_accepted_event(&self, offer_id: u64, seller: AccountId);
+    fn emit_collection_offer_made_event(
+        &self,
+        offer_id: u64,
+        contract: AccountId,
+        buyer: AccountId,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+        attribute: Option<(String, String)>,
+    );
+    fn emit_collection_offer_accepted_event(&self, offer_id: u64, token_id: Id, seller: AccountId);
+}
+
+impl<T> MarketplaceOffer for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Makes an offer for a NFT. Offered amount is held by the marketplace until
+    /// the offer is accepted or cancelled.
+    default fn make_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError> {
+        ensure!(
+            self.data::<Data>()
+                .registered_collections
+                .contains(&contract_address),
+            MarketplaceError::NotRegisteredContract
+        );
+        ensure!(
+            expiry > Self::env().block_timestamp(),
+            MarketplaceError::OfferExpired
+        );
+        let value = Self::env().transferred_value();
+        ensure!(value > 0, MarketplaceError::BadBuyValue);
+        ensure!(
+            value >= self.data::<Data>().min_offer_price,
+            MarketplaceError::OfferTooLow
+        );
+        self.make_room_for_offer(contract_address, token_id.clone(), value)?;
+
+        let buyer = Self::env().caller();
+        let offer_id = self.data::<Data>().last_offer_id.saturating_add(1);
+        self.data::<Data>().offers.insert(
+            &offer_id,
+            &Offer {
+                contract: contract_address,
+                token_id: token_id.clone(),
+                buyer,
+                price: value,
+                expiry,
+            },
+        );
+
+        let mut token_offers = self.get_offers(contract_address, token_id.clone());
+        token_offers.push(offer_id);
+        self.data::<Data>()
+            .token_offers
+            .insert(&(contract_address, token_id.clone()), &token_offers);
+        self.data::<Data>().last_offer_id = offer_id;
+
+        self.emit_offer_made_event(offer_id, contract_address, token_id, buyer, value, expiry);
+        Ok(offer_id)
+    }
+
+    /// Cancels an offer and credits the offered amount to the buyer's pending balance.
+    #[modifiers(non_reentrant)]
+    default fn cancel_offer(&mut self, offer_id: u64) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferNotFound)?;
+        ensure!(
+            offer.buyer == Self::env().caller(),
+            MarketplaceError::NotOfferOwner
+        );
+
+        self.remove_offer(offer_id, &offer);
+        self.add_pending_balance(offer.buyer, offer.price);
+        self.emit_offer_cancelled_event(offer_id);
+        Ok(())
+    }
+
+    /// Accepts an offer, transferring NFT from the caller to the offer maker.
+    #[modifiers(non_reentrant)]
+    default fn accept_offer(&mut self, offer_id: u64) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() < offer.expiry,
+            MarketplaceError::OfferExpired
+        );
+        self.check_token_owner(offer.contract, offer.token_id.clone())?;
+
+        let caller = Self::env().caller();
+        ensure!(caller != offer.buyer, MarketplaceError::AlreadyOwner);
+
+        self.remove_offer(offer_id, &offer);
+        self.sell_and_delist_token(
+            offer.contract,
+            offer.token_id.clone(),
+            caller,
+            offer.buyer,
+            offer.price,
+            None,
+            None,
+        )?;
+        self.emit_offer_accepted_event(offer_id, caller);
+        Ok(())
+    }
+
+    /// Gets an offer.
+    default fn get_offer(&self, offer_id: u64) -> Option<Offer> {
+        self.data::<Data>().offers.get(&offer_id)
+    }
+
+    /// Gets ids of all offers made for a NFT.
+    default fn get_offers(&self, contract_address: AccountId, token_id: Id) -> Vec<u64> {
+        self.data::<Data>()
+            .token_offers
+            .get(&(contract_address, token_id))
+            .unwrap_or_default()
+    }
+
+    /// Makes an offer to buy `quantity` of any NFTs from a collection for `price` each.
+    /// Transferred value must be equal to `price * quantity`.
+    default fn make_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError> {
+        self.add_collection_offer(contract_address, price, quantity, expiry, None)
+    }
+
+    /// Makes an offer to buy `quantity` of NFTs from a collection which have attribute
+    /// `key` set to `value` for `price` each. Transferred value must be equal to `price * quantity`.
+    default fn make_trait_offer(
+        &mut self,
+        contract_address: AccountId,
+        key: String,
+        value: String,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError> {
+        self.add_collection_offer(
+            contract_address,
+            price,
+            quantity,
+            expiry,
+            Some((key, value)),
+        )
+    }
+
+    /// Cancels a collection offer and credits the amount for unfilled quantity
+    /// to the buyer's pending balance.
+    #[modifiers(non_reentrant)]
+    default fn cancel_collection_offer(&mut self, offer_id: u64) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .collection_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferNotFound)?;
+        ensure!(
+            offer.buyer == Self::env().caller(),
+            MarketplaceError::NotOfferOwner
+        );
+
+        self.remove_collection_offer(offer_id, offer.contract);
+        self.add_pending_balance(offer.buyer, offer.price * offer.quantity as u128);
+        self.emit_offer_cancelled_event(offer_id);
+        Ok(())
+    }
+
+    /// Sells a NFT to the highest active collection offer without attribute constraint.
+    /// Fails if the offer price is below `min_price`.
+    #[modifiers(non_reentrant)]
+    default fn accept_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        min_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let offer_id = self
+            .best_collection_offer(contract_address)
+            .ok_or(MarketplaceError::NoCollectionOffer)?;
+        let offer = self
+            .data::<Data>()
+            .collection_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferNotFound)?;
+        ensure!(
+            offer.price >= min_price,
+            MarketplaceError::OfferBelowMinPrice
+        );
+        self.check_token_owner(contract_address, token_id.clone())?;
+
+        self.fill_collection_offer(offer_id, offer, token_id)
+    }
+
+    /// Sells a NFT to a trait offer. NFT attribute is verified with the NFT contract.
+    #[modifiers(non_reentrant)]
+    default fn accept_trait_offer(
+        &mut self,
+        offer_id: u64,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let offer = self
+            .data::<Data>()
+            .collection_offers
+            .get(&offer_id)
+            .ok_or(MarketplaceError::OfferNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() < offer.expiry,
+            MarketplaceError::OfferExpired
+        );
+        let (key, value) = offer
+            .attribute
+            .clone()
+            .ok_or(MarketplaceError::NotTraitOffer)?;
+        self.check_token_owner(offer.contract, token_id.clone())?;
+
+        ensure!(
+            PSP34MetadataRef::get_attribute(&offer.contract, token_id.clone(), key) == Some(value),
+            MarketplaceError::AttributeMismatch
+        );
+
+        self.fill_collection_offer(offer_id, offer, token_id)
+    }
+
+    /// Gets a collection offer.
+    default fn get_collection_offer(&self, offer_id: u64) -> Option<CollectionOffer> {
+        self.data::<Data>().collection_offers.get(&offer_id)
+    }
+
+    /// Gets ids of all offers made for a collection.
+    default fn get_collection_offers(&self, contract_address: AccountId) -> Vec<u64> {
+        self.data::<Data>()
+            .collection_offer_ids
+            .get(&contract_address)
+            .unwrap_or_default()
+    }
+
+    /// Sets min amount of an offer.
+    #[modifiers(only_owner)]
+    default fn set_min_offer_price(
+        &mut self,
+        min_offer_price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        self.data::<Data>().min_offer_price = min_offer_price;
+        Ok(())
+    }
+
+    /// Gets min amount of an offer.
+    default fn get_min_offer_price(&self) -> Balance {
+        self.data::<Data>().min_offer_price
+    }
+}
+
+impl<T> OfferInternal for T
+where
+    T: Storage<Data>,
+{
+    default fn remove_offer(&mut self, offer_id: u64, offer: &Offer) {
+        let key = (offer.contract, offer.token_id.clone());
+        let mut token_offers = self
+            .data::<Data>()
+            .token_offers
+            .get(&key)
+            .unwrap_or_default();
+        token_offers.retain(|id| *id != offer_id);
+
+        if token_offers.is_empty() {
+            self.data::<Data>().token_offers.remove(&key);
+        } else {
+            self.data::<Data>().token_offers.insert(&key, &token_offers);
+        }
+        self.data::<Data>().offers.remove(&offer_id);
+    }
+
+    default fn make_room_for_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let token_offers = self
+            .data::<Data>()
+            .token_offers
+            .get(&(contract_address, token_id))
+            .unwrap_or_default();
+        if token_offers.len() < MAX_OFFERS_PER_TOKEN {
+            return Ok(())
+        }
+
+        let now = Self::env().block_timestamp();
+        let mut lowest: Option<(u64, Offer)> = None;
+        for offer_id in token_offers {
+            if let Some(offer) = self.data::<Data>().offers.get(&offer_id) {
+                if offer.expiry <= now {
+                    lowest = Some((offer_id, offer));
+                    break
+                }
+                let is_lower = match &lowest {
+                    Some((_, lowest_offer)) => offer.price < lowest_offer.price,
+                    None => true,
+                };
+                if is_lower {
+                    lowest = Some((offer_id, offer));
+                }
+            }
+        }
+
+        let (offer_id, offer) = lowest.ok_or(MarketplaceError::OfferTooLow)?;
+        ensure!(
+            offer.expiry <= now || offer.price < price,
+            MarketplaceError::OfferTooLow
+        );
+        self.remove_offer(offer_id, &offer);
+        self.add_pending_balance(offer.buyer, offer.price);
+        self.emit_offer_cancelled_event(offer_id);
+        Ok(())
+    }
+
+    default fn remove_collection_offer(&mut self, offer_id: u64, contract_address: AccountId) {
+        let mut collection_offers = self
+            .data::<Data>()
+            .collection_offer_ids
+            .get(&contract_address)
+            .unwrap_or_default();
+        collection_offers.retain(|id| *id != offer_id);
+
+        if collection_offers.is_empty() {
+            self.data::<Data>()
+                .collection_offer_ids
+                .remove(&contract_address);
+        } else {
+            self.data::<Data>()
+                .collection_offer_ids
+                .insert(&contract_address, &collection_offers);
+        }
+        self.data::<Data>().collection_offers.remove(&offer_id);
+    }
+
+    default fn make_room_for_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+    ) -> Result<(), MarketplaceError> {
+        let now = Self::env().block_timestamp();
+        let mut offers = Vec::new();
+        for offer_id in self
+            .data::<Data>()
+            .collection_offer_ids
+            .get(&contract_address)
+            .unwrap_or_default()
+        {
+            if let Some(offer) = self.data::<Data>().collection_offers.get(&offer_id) {
+                if offer.expiry <= now {
+                    self.remove_collection_offer(offer_id, contract_address);
+                    self.add_pending_balance(offer.buyer, offer.price * offer.quantity as u128);
+                    self.emit_offer_cancelled_event(offer_id);
+                } else {
+                    offers.push((offer_id, offer));
+                }
+            }
+        }
+        if offers.len() < MAX_COLLECTION_OFFERS {
+            return Ok(())
+        }
+
+        let (offer_id, offer) = offers
+            .into_iter()
+            .min_by_key(|(_, offer)| offer.price)
+            .ok_or(MarketplaceError::OfferTooLow)?;
+        ensure!(offer.price < price, MarketplaceError::OfferTooLow);
+        self.remove_collection_offer(offer_id, contract_address);
+        self.add_pending_balance(offer.buyer, offer.price * offer.quantity as u128);
+        self.emit_offer_cancelled_event(offer_id);
+        Ok(())
+    }
+
+    default fn add_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+        attribute: Option<(String, String)>,
+    ) -> Result<u64, MarketplaceError> {
+        ensure!(
+            self.data::<Data>()
+                .registered_collections
+                .contains(&contract_address),
+            MarketplaceError::NotRegisteredContract
+        );
+        ensure!(
+            expiry > Self::env().block_timestamp(),
+            MarketplaceError::OfferExpired
+        );
+        let value = Self::env().transferred_value();
+        ensure!(
+            price > 0 && quantity > 0 && price.checked_mul(quantity as u128) == Some(value),
+            MarketplaceError::BadBuyValue
+        );
+        ensure!(
+            price >= self.data::<Data>().min_offer_price,
+            MarketplaceError::OfferTooLow
+        );
+        self.make_room_for_collection_offer(contract_address, price)?;
+
+        let buyer = Self::env().caller();
+        let offer_id = self.data::<Data>().last_offer_id.saturating_add(1);
+        self.data::<Data>().collection_offers.insert(
+            &offer_id,
+            &CollectionOffer {
+                contract: contract_address,
+                buyer,
+                price,
+                quantity,
+                expiry,
+                attribute: attribute.clone(),
+            },
+        );
+
+        let mut collection_offers = self
+            .data::<Data>()
+            .collection_offer_ids
+            .get(&contract_address)
+            .unwrap_or_default();
+        collection_offers.push(offer_id);
+        self.data::<Data>()
+            .collection_offer_ids
+            .insert(&contract_address, &collection_offers);
+        self.data::<Data>().last_offer_id = offer_id;
+
+        self.emit_collection_offer_made_event(
+            offer_id,
+            contract_address,
+            buyer,
+            price,
+            quantity,
+            expiry,
+            attribute,
+        );
+        Ok(offer_id)
+    }
+
+    default fn fill_collection_offer(
+        &mut self,
+        offer_id: u64,
+        mut offer: CollectionOffer,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError> {
+        let caller = Self::env().caller();
+        ensure!(caller != offer.buyer, MarketplaceError::AlreadyOwner);
+
+        offer.quantity -= 1;
+        if offer.quantity == 0 {
+            self.remove_collection_offer(offer_id, offer.contract);
+        } else {
+            self.data::<Data>()
+                .collection_offers
+                .insert(&offer_id, &offer);
+        }
+
+        self.sell_and_delist_token(
+            offer.contract,
+            token_id.clone(),
+            caller,
+            offer.buyer,
+            offer.price,
+            None,
+            None,
+        )?;
+        self.emit_collection_offer_accepted_event(offer_id, token_id, caller);
+        Ok(())
+    }
+
+    default fn best_collection_offer(&self, contract_address: AccountId) -> Option<u64> {
+        let now = Self::env().block_timestamp();
+        let mut best: Option<(u64, Balance)> = None;
+
+        for offer_id in self
+            .data::<Data>()
+            .collection_offer_ids
+            .get(&contract_address)
+            .unwrap_or_default()
+        {
+            if let Some(offer) = self.data::<Data>().collection_offers.get(&offer_id) {
+                let is_better = match best {
+                    Some((_, best_price)) => offer.price > best_price,
+                    None => true,
+                };
+                if offer.expiry > now && offer.attribute.is_none() && is_better {
+                    best = Some((offer_id, offer.price));
+                }
+            }
+        }
+
+        best.map(|(offer_id, _)| offer_id)
+    }
+}
+
+impl<T> OfferEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_offer_made_event(
+        &self,
+        _offer_id: u64,
+        _contract: AccountId,
+        _token_id: Id,
+        _buyer: AccountId,
+        _price: Balance,
+        _expiry: Timestamp,
+    ) {
+    }
+
+    default fn emit_offer_cancelled_event(&self, _offer_id: u64) {}
+
+    default fn emit_offer_accepted_event(&self, _offer_id: u64, _seller: AccountId) {}
+
+    default fn emit_collection_offer_made_event(
+        &self,
+        _offer_id: u64,
+        _contract: AccountId,
+        _buyer: AccountId,
+        _price: Balance,
+        _quantity: u32,
+        _expiry: Timestamp,
+        _attribute: Option<(String, String)>,
+    ) {
+    }
+
+    default fn emit_collection_offer_accepted_event(
+        &self,
+        _offer_id: u64,
+        _token_id: Id,
+        _seller: AccountId,
+    ) {
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2022 Astar Network
+//
+// Permission is hereby granted, free of charge, to any person obtaining
+// a copy of this software and associated documentation files (the"Software"),
+// to deal in the Software without restriction, including
+// without limitation the rights to use, copy, modify, merge, publish,
+// distribute, sublicense, and/or sell copies of the Software, and to
+// permit persons to whom the Software is furnished to do so, subject to
+// the following conditions:
+//
+// The above copyright notice and this permission notice shall be
+// included in all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+// EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+// MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND
+// NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE
+// LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION
+// OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION
+// WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+use super::marketplace_sale::Internal;
+use crate::{
+    ensure,
+    impls::marketplace::types::{
+        Data,
+        MarketplaceError,
+        Swap,
+        SwapTarget,
+        MAX_SWAP_SIZE,
+    },
+    traits::swap::MarketplaceSwap,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::{
+        ownable::*,
+        psp34::*,
+        reentrancy_guard::*,
+    },
+    modifiers,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+        Timestamp,
+    },
+};
+
+pub trait SwapInternal {
+    /// Checks if token list is not empty, not too long and has no duplicates.
+    fn check_swap_tokens(&self, tokens: &[(AccountId, Id)]) -> Result<(), MarketplaceError>;
+
+    /// Checks if all offered tokens are still owned by the swap maker.
+    fn is_swap_valid(&self, swap: &Swap) -> bool;
+}
+
+pub trait SwapEvents {
+    fn emit_swap_made_event(
+        &self,
+        swap_id: u64,
+        maker: AccountId,
+        top_up: Balance,
+        expiry: Timestamp,
+    );
+    fn emit_swap_cancelled_event(&self, swap_id: u64);
+    fn emit_swap_accepted_event(&self, swap_id: u64, taker: AccountId);
+}
+
+impl<T> MarketplaceSwap for T
+where
+    T: Storage<Data> + Storage<ownable::Data> + Storage<reentrancy_guard::Data>,
+{
+    /// Creates an order to swap caller's NFTs for the target NFTs. Transferred value is
+    /// held by the marketplace as a top-up paid to the taker together with the NFTs.
+    /// Order can be accepted until `expiry`.
+    default fn make_swap(
+        &mut self,
+        offered: Vec<(AccountId, Id)>,
+        target: SwapTarget,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError> {
+        ensure!(
+            expiry > Self::env().block_timestamp(),
+            MarketplaceError::SwapExpired
+        );
+        self.check_swap_tokens(&offered)?;
+        match &target {
+            SwapTarget::Tokens(tokens) => {
+                self.check_swap_tokens(tokens)?;
+                for (contract_address, _) in tokens.iter() {
+                    ensure!(
+                        self.data::<Data>()
+                            .registered_collections
+                            .contains(contract_address),
+                        MarketplaceError::NotRegisteredContract
+                    );
+                }
+            }
+            SwapTarget::Collection(contract_address) => {
+                ensure!(
+                    self.data::<Data>()
+                        .registered_collections
+                        .contains(contract_address),
+                    MarketplaceError::NotRegisteredContract
+                );
+            }
+        }
+
+        let maker = Self::env().caller();
+        for (contract_address, token_id) in offered.iter() {
+            self.check_token_owner(*contract_address, token_id.clone())?;
+            self.check_marketplace_approval(*contract_address, token_id.clone(), maker)?;
+        }
+
+        let top_up = Self::env().transferred_value();
+        let swap_id = self.data::<Data>().last_swap_id.saturating_add(1);
+        self.data::<Data>().swaps.insert(
+            &swap_id,
+            &Swap {
+                maker,
+                offered,
+                top_up,
+                target,
+                expiry,
+            },
+        );
+        self.data::<Data>().last_swap_id = swap_id;
+
+        self.emit_swap_made_event(swap_id, maker, top_up, expiry);
+        Ok(swap_id)
+    }
+
+    /// Cancels a swap order and credits the top-up to the maker's pending balance.
+    #[modifiers(non_reentrant)]
+    default fn cancel_swap(&mut self, swap_id: u64) -> Result<(), MarketplaceError> {
+        let swap = self
+            .data::<Data>()
+            .swaps
+            .get(&swap_id)
+            .ok_or(MarketplaceError::SwapNotFound)?;
+        ensure!(
+            swap.maker == Self::env().caller(),
+            MarketplaceError::NotOwner
+        );
+
+        self.data::<Data>().swaps.remove(&swap_id);
+        self.add_pending_balance(swap.maker, swap.top_up);
+        self.emit_swap_cancelled_event(swap_id);
+        Ok(())
+    }
+
+    /// Executes a swap order, exchanging caller's target NFTs for the offered NFTs and top-up.
+    /// `token_id` of the caller's NFT must be given if the swap targets a whole collection.
+    #[modifiers(non_reentrant)]
+    default fn accept_swap(
+        &mut self,
+        swap_id: u64,
+        token_id: Option<Id>,
+    ) -> Result<(), MarketplaceError> {
+        let swap = self
+            .data::<Data>()
+            .swaps
+            .get(&swap_id)
+            .ok_or(MarketplaceError::SwapNotFound)?;
+        ensure!(
+            Self::env().block_timestamp() < swap.expiry,
+            MarketplaceError::SwapExpired
+        );
+
+        let taker = Self::env().caller();
+        ensure!(taker != swap.maker, MarketplaceError::AlreadyOwner);
+
+        let wanted = match (&swap.target, token_id) {
+            (SwapTarget::Tokens(tokens), None) => tokens.clone(),
+            (SwapTarget::Collection(contract_address), Some(token_id)) => {
+                Vec::from([(*contract_address, token_id)])
+            }
+            _ => return Err(MarketplaceError::InvalidSwap),
+        };
+        for (contract_address, token_id) in wanted.iter() {
+            self.check_token_owner(*contract_address, token_id.clone())?;
+            self.check_marketplace_approval(*contract_address, token_id.clone(), taker)?;
+        }
+        ensure!(self.is_swap_valid(&swap), MarketplaceError::SwapInvalidated);
+
+        self.data::<Data>().swaps.remove(&swap_id);
+        for (contract_address, token_id) in swap.offered {
+            PSP34Ref::transfer(&contract_address, taker, token_id.clone(), Vec::new())
+                .map_err(|_| MarketplaceError::UnableToTransferToken)?;
+            self.delist_token(contract_address, token_id);
+        }
+
+        // Top-up pays for the target tokens, so marketplace fee and royalties are charged on it.
+        // Rounding remainder goes to the first token.
+        let share = swap.top_up / wanted.len() as u128;
+        let mut remainder = swap.top_up - share * wanted.len() as u128;
+        for (contract_address, token_id) in wanted {
+            self.sell_and_delist_token(
+                contract_address,
+                token_id,
+                taker,
+                swap.maker,
+                share + remainder,
+                None,
+                None,
+            )?;
+            remainder = 0;
+        }
+
+        self.emit_swap_accepted_event(swap_id, taker);
+        Ok(())
+    }
+
+    /// Gets a swap order.
+    default fn get_swap(&self, swap_id: u64) -> Option<Swap> {
+        self.data::<Data>().swaps.get(&swap_id)
+    }
+}
+
+impl<T> SwapInternal for T
+where
+    T: Storage<Data>,
+{
+    default fn check_swap_tokens(
+        &self,
+        tokens: &[(AccountId, Id)],
+    ) -> Result<(), MarketplaceError> {
+        ensure!(
+            !tokens.is_empty() && tokens.len() <= MAX_SWAP_SIZE,
+            MarketplaceError::InvalidSwap
+        );
+        for (index, token) in tokens.iter().enumerate() {
+            ensure!(
+                !tokens[..index].contains(token),
+                MarketplaceError::InvalidSwap
+            );
+        }
+        Ok(())
+    }
+
+    default fn is_swap_valid(&self, swap: &Swap) -> bool {
+        swap.offered.iter().all(|(contract_address, token_id)| {
+            PSP34Ref::owner_of(contract_address, token_id.clone()) == Some(swap.maker)
+        })
+    }
+}
+
+impl<T> SwapEvents for T
+where
+    T: Storage<Data>,
+{
+    default fn emit_swap_made_event(
+        &self,
+        _swap_id: u64,
+        _maker: AccountId,
+        _top_up: Balance,
+        _expiry: Timestamp,
+    ) {
+    }
+
+    default fn emit_swap_cancelled_event(&self, _swap_id: u64) {}
+
+    default fn emit_swap_accepted_event(&self, _swap_id: u64, _taker: AccountId) {}
+}
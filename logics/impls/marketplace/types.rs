@@ -1,3 +1,4 @@
+use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::{
         ownable::OwnableError,
@@ -10,6 +11,7 @@ use openbrush::{
         Balance,
         Hash,
         String,
+        Timestamp,
     },
 };
 use scale::{
@@ -19,16 +21,73 @@ use scale::{
 
 pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
 
+/// Max number of tokens in a bundle.
+pub const MAX_BUNDLE_SIZE: usize = 20;
+
+/// Max number of tokens on either side of a swap.
+pub const MAX_SWAP_SIZE: usize = 20;
+
+/// Max number of active offers for a single token.
+pub const MAX_OFFERS_PER_TOKEN: usize = 20;
+
+/// Max number of active offers for a collection, including trait offers.
+pub const MAX_COLLECTION_OFFERS: usize = 50;
+
+/// Max number of royalty receivers of a collection.
+pub const MAX_ROYALTY_RECEIVERS: usize = 10;
+
+/// Max number of membership fee discount tiers.
+pub const MAX_DISCOUNT_TIERS: usize = 10;
+
+/// Default delay of marketplace fee and fee recipient changes (1 day).
+pub const DEFAULT_FEE_CHANGE_DELAY: Timestamp = 24 * 60 * 60 * 1000;
+
+/// Max delay of marketplace fee and fee recipient changes (30 days).
+pub const MAX_FEE_CHANGE_DELAY: Timestamp = 30 * 24 * 60 * 60 * 1000;
+
 #[derive(Default, Debug)]
 #[openbrush::upgradeable_storage(STORAGE_KEY)]
 pub struct Data {
     pub registered_collections: Mapping<AccountId, RegisteredCollection>,
     pub items: Mapping<(AccountId, Id), Item>,
+    pub auctions: Mapping<(AccountId, Id), Auction>,
+    pub sealed_auctions: Mapping<u64, SealedAuction>,
+    pub sealed_auction_ids: Mapping<(AccountId, Id), u64>,
+    pub sealed_bids: Mapping<(u64, AccountId), SealedBid>,
+    pub last_sealed_auction_id: u64,
+    pub offers: Mapping<u64, Offer>,
+    pub token_offers: Mapping<(AccountId, Id), Vec<u64>>,
+    pub collection_offers: Mapping<u64, CollectionOffer>,
+    pub collection_offer_ids: Mapping<AccountId, Vec<u64>>,
+    pub last_offer_id: u64,
+    /// Min amount of an offer, or of a collection offer price per token.
+    pub min_offer_price: Balance,
+    pub bundles: Mapping<u64, Bundle>,
+    pub bundled_tokens: Mapping<(AccountId, Id), u64>,
+    pub last_bundle_id: u64,
+    pub swaps: Mapping<u64, Swap>,
+    pub last_swap_id: u64,
     pub fee: u16,
     pub max_fee: u16,
     pub market_fee_recipient: Option<AccountId>,
     pub nft_contract_hash: Mapping<NftContractType, Hash>,
     pub nonce: u64,
+    pub allowed_currencies: Vec<AccountId>,
+    pub pending_balances: Mapping<AccountId, Balance>,
+    pub token_royalties: Mapping<(AccountId, Id), u16>,
+    pub collection_fees: Mapping<AccountId, u16>,
+    pub referral_share: u16,
+    pub referral_earnings: Mapping<(AccountId, Option<AccountId>), Balance>,
+    pub membership_collection: Option<AccountId>,
+    /// Fee discounts in basis points by min number of membership tokens held, ascending.
+    pub discount_tiers: Vec<(u32, u16)>,
+    pub fee_change_delay: Timestamp,
+    /// Proposed marketplace fee and the time it can be set at.
+    pub pending_fee: Option<(u16, Timestamp)>,
+    /// Proposed marketplace fee recipient and the time it can be set at.
+    pub pending_fee_recipient: Option<(AccountId, Timestamp)>,
+    /// Proposed collection fees and the time they can be set at.
+    pub pending_collection_fees: Mapping<AccountId, (Option<u16>, Timestamp)>,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -48,12 +107,6 @@ pub enum MarketplaceError {
     NotRegisteredContract,
     /// Value send to buy method is invalid
     BadBuyValue,
-    /// Fee transfer to the marketplace failed.
-    TransferToMarketplaceFailed,
-    /// Fee transfer to the marketplace failed.
-    TransferToOwnerFailed,
-    /// Royalty transfer failed.
-    TransferToAuthorFailed,
     /// Contract has been already registered to the marketplace
     ContractAlreadyRegistered,
     /// Fee required is too high.
@@ -70,6 +123,108 @@ pub enum MarketplaceError {
     TokenDoesNotExist,
     /// Marketplace item is already listed for sale.
     ItemAlreadyListedForSale,
+    /// Token is already put up for an auction.
+    AuctionAlreadyExists,
+    /// Auction for the token does not exist.
+    AuctionNotFound,
+    /// Auction start time is not before its end time or the end time has passed.
+    InvalidAuctionPeriod,
+    /// Auction has not started yet.
+    AuctionNotStarted,
+    /// Auction has already ended.
+    AuctionEnded,
+    /// Auction has not ended yet.
+    AuctionNotEnded,
+    /// Auction already has a bid and can not be cancelled.
+    AuctionHasBids,
+    /// Bid is below the reserve price or not higher than the highest bid.
+    BidTooLow,
+    /// Refund to the bidder failed.
+    TransferToBidderFailed,
+    /// Dutch auction end price is higher than the start price or its period is invalid.
+    InvalidDutchAuction,
+    /// Refund of the overpaid amount to the buyer failed.
+    TransferToBuyerFailed,
+    /// Offer does not exist.
+    OfferNotFound,
+    /// Offer has expired.
+    OfferExpired,
+    /// Caller is not the account which made the offer.
+    NotOfferOwner,
+    /// There is no active offer for the collection.
+    NoCollectionOffer,
+    /// Offer does not have an attribute constraint.
+    NotTraitOffer,
+    /// Token attribute does not match the offer.
+    AttributeMismatch,
+    /// Bundle has too few or too many tokens, or contains a token twice.
+    InvalidBundleSize,
+    /// Token is already included in a bundle.
+    TokenAlreadyInBundle,
+    /// Bundle does not exist.
+    BundleNotFound,
+    /// Some of the bundle tokens are no longer owned by the bundle seller.
+    BundleInvalidated,
+    /// Listing start time is not before its expiration or the expiration has passed.
+    InvalidListingPeriod,
+    /// Listing has expired.
+    ListingExpired,
+    /// Listing has not started yet.
+    ListingNotStarted,
+    /// Current item price is higher than the maximum price buyer agreed to pay.
+    PriceExceedsMaxPrice,
+    /// PSP22 token is not allowed as a listing currency.
+    CurrencyNotAllowed,
+    /// PSP22 token is already allowed as a listing currency.
+    CurrencyAlreadyAllowed,
+    /// Token is no longer owned by the account that listed it.
+    ListingOwnerChanged,
+    /// Listing is still valid and can not be pruned.
+    ListingStillValid,
+    /// Marketplace is not approved to transfer the token.
+    MarketplaceNotApproved,
+    /// Listing is reserved for another buyer.
+    ListingReserved,
+    /// Swap order does not exist.
+    SwapNotFound,
+    /// Swap order offers or requests no tokens, too many tokens or duplicated tokens.
+    InvalidSwap,
+    /// Some of the swapped tokens are no longer owned by the swap maker.
+    SwapInvalidated,
+    /// Sealed auction is no longer accepting bid commitments.
+    CommitPhaseEnded,
+    /// Sealed auction is not accepting bid reveals.
+    RevealPhaseNotActive,
+    /// Caller has already committed a bid.
+    BidAlreadyCommitted,
+    /// Caller has no committed bid.
+    BidNotFound,
+    /// Revealed bid does not match the commitment, exceeds the deposit or is below reserve price.
+    InvalidReveal,
+    /// PSP22 payment could not be transferred from the buyer.
+    TransferFromBuyerFailed,
+    /// Caller has no proceeds to withdraw.
+    NothingToWithdraw,
+    /// Transfer of withdrawn proceeds failed.
+    WithdrawFailed,
+    /// Royalty receivers are empty, too many or their shares don't sum to 10 000.
+    InvalidRoyaltySplit,
+    /// Discount tiers are not ascending, too many or a discount exceeds 10 000.
+    InvalidDiscountTiers,
+    /// There is no pending fee change to execute or cancel.
+    NoPendingFeeChange,
+    /// Fee change delay has not passed yet.
+    FeeChangeNotReady,
+    /// Fee change delay can only be increased up to the max delay.
+    InvalidFeeChangeDelay,
+    /// Offer is below the min offer price or, when offer limit is reached, not higher than the lowest offer.
+    OfferTooLow,
+    /// Best collection offer price is below the min price seller agreed to sell at.
+    OfferBelowMinPrice,
+    /// Referrer is the buyer or the seller.
+    InvalidReferrer,
+    /// Swap order has expired.
+    SwapExpired,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -85,7 +240,8 @@ pub enum NftContractType {
     derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
 )]
 pub struct RegisteredCollection {
-    pub royalty_receiver: AccountId,
+    /// Royalty receivers with their share of the royalty in basis points.
+    pub royalty_receivers: Vec<(AccountId, u16)>,
     pub marketplace_ipfs: String,
     pub royalty: u16,
 }
@@ -98,6 +254,163 @@ pub struct RegisteredCollection {
 pub struct Item {
     pub owner: AccountId,
     pub price: Balance,
+    pub dutch_auction: Option<DutchAuction>,
+    pub starts_at: Option<Timestamp>,
+    pub expires_at: Option<Timestamp>,
+    /// PSP22 token the price is set in, `None` for the native currency.
+    pub currency: Option<AccountId>,
+    /// Whether the token is held by the marketplace until sold or unlisted.
+    pub escrowed: bool,
+    /// The only account allowed to buy the item, `None` for a public sale.
+    pub reserved_for: Option<AccountId>,
+}
+
+/// Optional settings of a listing.
+#[derive(Encode, Decode, Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct ListingOptions {
+    /// Time the sale starts at, immediately if `None`.
+    pub starts_at: Option<Timestamp>,
+    /// Time the sale expires at, never if `None`.
+    pub expires_at: Option<Timestamp>,
+    /// Allowed PSP22 token the price is set in, `None` for the native currency.
+    pub currency: Option<AccountId>,
+    /// Whether the token is transferred to the marketplace until it is sold or unlisted.
+    pub escrow: bool,
+    /// The only account allowed to buy the item, `None` for a public sale.
+    pub reserved_for: Option<AccountId>,
+}
+
+/// Linear price decay from `Item::price` down to `end_price` between
+/// `start_time` and `end_time`.
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct DutchAuction {
+    pub end_price: Balance,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+}
+
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Auction {
+    pub seller: AccountId,
+    pub reserve_price: Balance,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub highest_bidder: Option<AccountId>,
+    pub highest_bid: Balance,
+    /// Referrer of the highest bid.
+    pub referrer: Option<AccountId>,
+}
+
+/// Auction where bids are committed as hashes until `commit_end` and revealed
+/// until `reveal_end`. With `second_price` set the winner pays the second highest bid.
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SealedAuction {
+    pub contract: AccountId,
+    pub token_id: Id,
+    pub seller: AccountId,
+    pub reserve_price: Balance,
+    pub commit_end: Timestamp,
+    pub reveal_end: Timestamp,
+    pub second_price: bool,
+    pub bids: u32,
+    pub highest_bidder: Option<AccountId>,
+    pub highest_bid: Balance,
+    pub second_bid: Balance,
+}
+
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct SealedBid {
+    pub commitment: Hash,
+    pub deposit: Balance,
+    pub revealed: bool,
+    pub referrer: Option<AccountId>,
+}
+
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Offer {
+    pub contract: AccountId,
+    pub token_id: Id,
+    pub buyer: AccountId,
+    pub price: Balance,
+    pub expiry: Timestamp,
+}
+
+/// Offer to buy `quantity` of any tokens from a collection for `price` each.
+/// If `attribute` is set, only tokens having attribute `key` equal to `value` can be sold.
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct CollectionOffer {
+    pub contract: AccountId,
+    pub buyer: AccountId,
+    pub price: Balance,
+    pub quantity: u32,
+    pub expiry: Timestamp,
+    pub attribute: Option<(String, String)>,
+}
+
+/// Tokens sold together for a single price. Each token is paired with a weight used
+/// to apportion the price between tokens, the price is split evenly if all weights are zero.
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Bundle {
+    pub owner: AccountId,
+    pub price: Balance,
+    pub tokens: Vec<(AccountId, Id, u32)>,
+}
+
+/// Tokens requested in exchange for a swap.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub enum SwapTarget {
+    /// All of the listed tokens.
+    Tokens(Vec<(AccountId, Id)>),
+    /// Any single token from the collection.
+    Collection(AccountId),
+}
+
+/// Order to exchange maker's tokens, together with an optional native top-up,
+/// for the target tokens.
+#[derive(Encode, Decode, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "std",
+    derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+)]
+pub struct Swap {
+    pub maker: AccountId,
+    pub offered: Vec<(AccountId, Id)>,
+    pub top_up: Balance,
+    pub target: SwapTarget,
+    pub expiry: Timestamp,
 }
 
 impl From<OwnableError> for MarketplaceError {
@@ -0,0 +1,114 @@
+use crate::impls::marketplace::types::{
+    Auction,
+    MarketplaceError,
+    SealedAuction,
+    SealedBid,
+};
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{
+        AccountId,
+        Balance,
+        Hash,
+        Timestamp,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceAuction {
+    /// Puts a NFT up for an English auction on the marketplace.
+    #[ink(message)]
+    fn create_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Places a bid on an auctioned NFT. Previous highest bid is refunded.
+    /// `referrer` of the winning bid receives the referral share of the marketplace fee.
+    #[ink(message, payable)]
+    fn place_bid(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Settles an ended auction, transferring NFT to the highest bidder.
+    #[ink(message)]
+    fn settle_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels an auction which has not received any bids yet.
+    #[ink(message)]
+    fn cancel_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets an auction for the NFT.
+    #[ink(message)]
+    fn get_auction(&self, contract_address: AccountId, token_id: Id) -> Option<Auction>;
+
+    /// Puts a NFT up for a sealed-bid auction. Bids are committed until `commit_end`
+    /// and revealed until `reveal_end`. With `second_price` set the winner pays the
+    /// second highest revealed bid (or the reserve price) instead of their own bid.
+    #[ink(message)]
+    fn create_sealed_auction(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        reserve_price: Balance,
+        commit_end: Timestamp,
+        reveal_end: Timestamp,
+        second_price: bool,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Commits a sealed bid. `commitment` is a `Blake2x256` hash of SCALE encoded
+    /// `(bidder, bid, salt)` and transferred value is held as a deposit covering the bid.
+    /// `referrer` of the winning bid receives the referral share of the marketplace fee.
+    #[ink(message, payable)]
+    fn commit_sealed_bid(
+        &mut self,
+        auction_id: u64,
+        commitment: Hash,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Reveals a committed sealed bid.
+    #[ink(message)]
+    fn reveal_sealed_bid(
+        &mut self,
+        auction_id: u64,
+        bid: Balance,
+        salt: Hash,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Settles a sealed auction after the reveal phase, transferring NFT to the highest bidder.
+    #[ink(message)]
+    fn settle_sealed_auction(&mut self, auction_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Cancels a sealed auction which has not received any bid commitments yet.
+    #[ink(message)]
+    fn cancel_sealed_auction(&mut self, auction_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Credits the deposit of a bid which has not won a sealed auction
+    /// to the bidder's pending balance.
+    #[ink(message)]
+    fn withdraw_sealed_bid(&mut self, auction_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets a sealed auction.
+    #[ink(message)]
+    fn get_sealed_auction(&self, auction_id: u64) -> Option<SealedAuction>;
+
+    /// Gets a bid committed to a sealed auction.
+    #[ink(message)]
+    fn get_sealed_bid(&self, auction_id: u64, bidder: AccountId) -> Option<SealedBid>;
+}
@@ -0,0 +1,42 @@
+use crate::impls::marketplace::types::{
+    Bundle,
+    MarketplaceError,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{
+        AccountId,
+        Balance,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceBundle {
+    /// Creates a sale of several NFTs for a single price. Each NFT is paired with
+    /// a weight used to apportion the price, the price is split evenly if all weights are zero.
+    #[ink(message)]
+    fn list_bundle(
+        &mut self,
+        tokens: Vec<(AccountId, Id, u32)>,
+        price: Balance,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Removes a bundle from the marketplace sale. Bundle can be removed by anyone
+    /// once some of its NFTs are no longer owned by the seller.
+    #[ink(message)]
+    fn unlist_bundle(&mut self, bundle_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Buys all NFTs from a bundle. Value transferred above the bundle price is
+    /// credited to the buyer's pending balance.
+    #[ink(message, payable)]
+    fn buy_bundle(&mut self, bundle_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets a bundle.
+    #[ink(message)]
+    fn get_bundle(&self, bundle_id: u64) -> Option<Bundle>;
+
+    /// Gets id of a bundle containing the NFT.
+    #[ink(message)]
+    fn get_token_bundle(&self, contract_address: AccountId, token_id: Id) -> Option<u64>;
+}
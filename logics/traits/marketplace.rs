@@ -1,8 +1,11 @@
 use crate::impls::marketplace::types::{
+    Item,
+    ListingOptions,
     MarketplaceError,
     NftContractType,
     RegisteredCollection,
 };
+use ink::prelude::vec::Vec;
 use openbrush::{
     contracts::psp34::Id,
     traits::{
@@ -10,17 +13,21 @@ use openbrush::{
         Balance,
         Hash,
         String,
+        Timestamp,
     },
 };
 
 #[openbrush::trait_definition]
 pub trait MarketplaceSale {
     /// Adds a NFT contract to the marketplace.
+    /// NFT contract supports a single royalty receiver, so it reports the whole `royalty`
+    /// for the first of `royalty_receivers`. Sales on the marketplace split the royalty
+    /// between all registered receivers instead.
     #[ink(message)]
     fn factory(
         &mut self,
         marketplace_ipfs: String,
-        royalty_receiver: AccountId,
+        royalty_receivers: Vec<(AccountId, u16)>,
         royalty: u16,
         nft_name: String,
         nft_symbol: String,
@@ -51,21 +58,113 @@ pub trait MarketplaceSale {
         price: Balance,
     ) -> Result<(), MarketplaceError>;
 
-    /// Removes a NFT from the marketplace sale.
+    /// Creates a NFT item sale on the marketplace. Sale can optionally be
+    /// scheduled, priced in an allowed PSP22 token, escrowed by the marketplace
+    /// or reserved for a single buyer, see `ListingOptions`.
+    #[ink(message)]
+    fn list_with_options(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        price: Balance,
+        options: ListingOptions,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Creates a NFT item sale with a price declining from `start_price`
+    /// to `end_price` between `start_time` and `end_time`.
+    #[ink(message)]
+    fn list_dutch(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        start_price: Balance,
+        end_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        currency: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Removes a NFT from the marketplace sale. A bundle containing the NFT is removed as well.
+    /// Escrowed token is returned to the seller.
     #[ink(message)]
     fn unlist(&mut self, contract_address: AccountId, token_id: Id)
         -> Result<(), MarketplaceError>;
 
     /// Buys NFT item from the marketplace.
+    /// Value transferred above the price is credited to the buyer's pending balance.
+    /// `referrer` receives the referral share of the marketplace fee.
+    #[ink(message, payable)]
+    fn buy(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Buys NFT item from the marketplace if its current price does not exceed `max_price`.
+    /// `referrer` receives the referral share of the marketplace fee.
+    #[ink(message, payable)]
+    fn buy_with_max_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        max_price: Balance,
+        referrer: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Updates price of a listed NFT item.
+    #[ink(message)]
+    fn update_price(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        new_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Removes a listing whose token is no longer owned by the lister. Can be called by anyone.
+    #[ink(message)]
+    fn prune_listing(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Creates sales of several NFT items on the marketplace. If `all_or_nothing` is set,
+    /// the call fails on the first failed item, otherwise a result for each item is returned.
+    #[ink(message)]
+    fn list_batch(
+        &mut self,
+        items: Vec<(AccountId, Id, Balance)>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<Result<(), MarketplaceError>>, MarketplaceError>;
+
+    /// Removes several NFTs from the marketplace sale. If `all_or_nothing` is set,
+    /// the call fails on the first failed item, otherwise a result for each item is returned.
+    #[ink(message)]
+    fn unlist_batch(
+        &mut self,
+        items: Vec<(AccountId, Id)>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<Result<(), MarketplaceError>>, MarketplaceError>;
+
+    /// Buys several NFT items from the marketplace. Each item is charged its current price,
+    /// transferred value must cover the sum of prices and any surplus is credited to the buyer's
+    /// pending balance.
+    /// If `all_or_nothing` is set, the call fails on the first failed item,
+    /// otherwise a result for each item is returned.
     #[ink(message, payable)]
-    fn buy(&mut self, contract_address: AccountId, token_id: Id) -> Result<(), MarketplaceError>;
+    fn buy_batch(
+        &mut self,
+        items: Vec<(AccountId, Id)>,
+        all_or_nothing: bool,
+    ) -> Result<Vec<Result<(), MarketplaceError>>, MarketplaceError>;
 
     /// Registers NFT collection to the marketplace.
     #[ink(message)]
     fn register(
         &mut self,
         contract_address: AccountId,
-        royalty_receiver: AccountId,
+        royalty_receivers: Vec<(AccountId, u16)>,
         royalty: u16,
         marketplace_ipfs: String,
     ) -> Result<(), MarketplaceError>;
@@ -77,22 +176,138 @@ pub trait MarketplaceSale {
         contract_address: AccountId,
     ) -> Option<RegisteredCollection>;
 
-    /// Sets the marketplace fee.
+    /// Sets royalty of a single token overriding the collection royalty.
+    /// Passing `None` removes the override.
+    #[ink(message)]
+    fn set_token_royalty(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        royalty: Option<u16>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets royalty charged on sale of the token.
+    #[ink(message)]
+    fn get_effective_royalty(&self, contract_address: AccountId, token_id: Id) -> Option<u16>;
+
+    /// Proposes a new marketplace fee which can be set once the fee change delay passes.
+    /// Fee decreases are applied immediately.
+    #[ink(message)]
+    fn propose_marketplace_fee(&mut self, fee: u16) -> Result<(), MarketplaceError>;
+
+    /// Sets the proposed marketplace fee after the fee change delay.
+    #[ink(message)]
+    fn execute_marketplace_fee(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Cancels the proposed marketplace fee.
+    #[ink(message)]
+    fn cancel_marketplace_fee(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Gets the proposed marketplace fee and the time it can be set at.
+    #[ink(message)]
+    fn get_pending_marketplace_fee(&self) -> Option<(u16, Timestamp)>;
+
+    /// Sets the delay of marketplace fee and fee recipient changes. The delay can only be increased.
+    #[ink(message)]
+    fn set_fee_change_delay(&mut self, delay: Timestamp) -> Result<(), MarketplaceError>;
+
+    /// Gets the delay of marketplace fee and fee recipient changes.
     #[ink(message)]
-    fn set_marketplace_fee(&mut self, fee: u16) -> Result<(), MarketplaceError>;
+    fn get_fee_change_delay(&self) -> Timestamp;
 
     /// Gets the marketplace fee.
     #[ink(message)]
     fn get_marketplace_fee(&self) -> u16;
 
+    /// Sets the share of the marketplace fee paid to referrers, in basis points.
+    #[ink(message)]
+    fn set_referral_share(&mut self, share: u16) -> Result<(), MarketplaceError>;
+
+    /// Gets the share of the marketplace fee paid to referrers, in basis points.
+    #[ink(message)]
+    fn get_referral_share(&self) -> u16;
+
+    /// Gets total referral fees earned by the referrer in the given currency.
+    #[ink(message)]
+    fn get_referral_earnings(&self, referrer: AccountId, currency: Option<AccountId>) -> Balance;
+
+    /// Sets PSP34 collection whose holders get a marketplace fee discount.
+    #[ink(message)]
+    fn set_membership_collection(
+        &mut self,
+        contract_address: Option<AccountId>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets PSP34 collection whose holders get a marketplace fee discount.
+    #[ink(message)]
+    fn get_membership_collection(&self) -> Option<AccountId>;
+
+    /// Sets fee discount tiers as `(min tokens held, discount in basis points)`
+    /// ordered by the number of tokens.
+    #[ink(message)]
+    fn set_discount_tiers(&mut self, tiers: Vec<(u32, u16)>) -> Result<(), MarketplaceError>;
+
+    /// Gets fee discount tiers.
+    #[ink(message)]
+    fn get_discount_tiers(&self) -> Vec<(u32, u16)>;
+
+    /// Gets marketplace fee discount of the account in basis points.
+    #[ink(message)]
+    fn get_fee_discount(&self, account: AccountId) -> u16;
+
+    /// Proposes the marketplace fee charged on sales of the collection instead of the global fee,
+    /// which can be set once the fee change delay passes. Passing `None` removes the override.
+    /// Changes which don't increase the collection fee are applied immediately.
+    #[ink(message)]
+    fn propose_collection_fee(
+        &mut self,
+        contract_address: AccountId,
+        fee: Option<u16>,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Sets the proposed collection fee after the fee change delay.
+    #[ink(message)]
+    fn execute_collection_fee(
+        &mut self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Cancels the proposed collection fee.
+    #[ink(message)]
+    fn cancel_collection_fee(
+        &mut self,
+        contract_address: AccountId,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Gets the proposed collection fee and the time it can be set at.
+    #[ink(message)]
+    fn get_pending_collection_fee(
+        &self,
+        contract_address: AccountId,
+    ) -> Option<(Option<u16>, Timestamp)>;
+
+    /// Gets the marketplace fee charged on sales of the collection.
+    #[ink(message)]
+    fn get_effective_fee(&self, contract_address: AccountId) -> u16;
+
     /// Gets max fee that can be applied to an item price.
     #[ink(message)]
     fn get_max_fee(&self) -> u16;
 
-    /// Checks if NFT token is listed on the marketplace and returns token price.
+    /// Checks if NFT token is listed on the marketplace and returns current token price.
+    /// Returns `None` if the sale has not started yet or has expired.
     #[ink(message)]
     fn get_price(&self, contract_address: AccountId, token_id: Id) -> Option<Balance>;
 
+    /// Gets NFT item listing.
+    #[ink(message)]
+    fn get_listing(&self, contract_address: AccountId, token_id: Id) -> Option<Item>;
+
+    /// Checks if listed NFT item can currently be bought: listing is active, collection
+    /// is registered, token is still owned by the lister and marketplace can transfer it.
+    #[ink(message)]
+    fn is_listing_fillable(&self, contract_address: AccountId, token_id: Id) -> bool;
+
     /// Sets contract metadata (ipfs url)
     #[ink(message)]
     fn set_contract_metadata(
@@ -105,7 +320,43 @@ pub trait MarketplaceSale {
     #[ink(message)]
     fn get_fee_recipient(&self) -> AccountId;
 
-    /// Sets the marketplace fee recipient.
+    /// Proposes a new marketplace fee recipient which can be set once the fee change delay passes.
+    #[ink(message)]
+    fn propose_fee_recipient(&mut self, fee_recipient: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Sets the proposed marketplace fee recipient after the fee change delay.
+    #[ink(message)]
+    fn execute_fee_recipient(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Cancels the proposed marketplace fee recipient.
+    #[ink(message)]
+    fn cancel_fee_recipient(&mut self) -> Result<(), MarketplaceError>;
+
+    /// Gets the proposed marketplace fee recipient and the time it can be set at.
+    #[ink(message)]
+    fn get_pending_fee_recipient(&self) -> Option<(AccountId, Timestamp)>;
+
+    /// Allows PSP22 token to be used as a listing currency.
+    #[ink(message)]
+    fn add_currency(&mut self, currency: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Disallows PSP22 token as a listing currency.
+    #[ink(message)]
+    fn remove_currency(&mut self, currency: AccountId) -> Result<(), MarketplaceError>;
+
+    /// Gets PSP22 tokens allowed as listing currencies.
+    #[ink(message)]
+    fn get_currencies(&self) -> Vec<AccountId>;
+
+    /// Withdraws caller's pending proceeds to the caller.
+    #[ink(message)]
+    fn withdraw(&mut self) -> Result<Balance, MarketplaceError>;
+
+    /// Withdraws caller's pending proceeds to the `to` account.
+    #[ink(message)]
+    fn withdraw_to(&mut self, to: AccountId) -> Result<Balance, MarketplaceError>;
+
+    /// Gets proceeds and refunds of the account waiting to be withdrawn.
     #[ink(message)]
-    fn set_fee_recipient(&mut self, fee_recipient: AccountId) -> Result<(), MarketplaceError>;
+    fn pending_balance(&self, account: AccountId) -> Balance;
 }
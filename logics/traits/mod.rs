@@ -1 +1,5 @@
+pub mod auction;
+pub mod bundle;
 pub mod marketplace;
+pub mod offer;
+pub mod swap;
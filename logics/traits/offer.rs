@@ -0,0 +1,103 @@
+use crate::impls::marketplace::types::{
+    CollectionOffer,
+    MarketplaceError,
+    Offer,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{
+        AccountId,
+        Balance,
+        String,
+        Timestamp,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceOffer {
+    /// Makes an offer for a NFT. Offered amount is held by the marketplace until
+    /// the offer is accepted or cancelled.
+    #[ink(message, payable)]
+    fn make_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Cancels an offer and credits the offered amount to the buyer's pending balance.
+    #[ink(message)]
+    fn cancel_offer(&mut self, offer_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Accepts an offer, transferring NFT from the caller to the offer maker.
+    #[ink(message)]
+    fn accept_offer(&mut self, offer_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Gets an offer.
+    #[ink(message)]
+    fn get_offer(&self, offer_id: u64) -> Option<Offer>;
+
+    /// Gets ids of all offers made for a NFT.
+    #[ink(message)]
+    fn get_offers(&self, contract_address: AccountId, token_id: Id) -> Vec<u64>;
+
+    /// Makes an offer to buy `quantity` of any NFTs from a collection for `price` each.
+    /// Transferred value must be equal to `price * quantity`.
+    #[ink(message, payable)]
+    fn make_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Cancels a collection offer and credits the amount for unfilled quantity
+    /// to the buyer's pending balance.
+    #[ink(message)]
+    fn cancel_collection_offer(&mut self, offer_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Sells a NFT to the highest active collection offer without attribute constraint.
+    /// Fails if the offer price is below `min_price`.
+    #[ink(message)]
+    fn accept_collection_offer(
+        &mut self,
+        contract_address: AccountId,
+        token_id: Id,
+        min_price: Balance,
+    ) -> Result<(), MarketplaceError>;
+
+    /// Makes an offer to buy `quantity` of NFTs from a collection which have attribute
+    /// `key` set to `value` for `price` each. Transferred value must be equal to `price * quantity`.
+    #[ink(message, payable)]
+    fn make_trait_offer(
+        &mut self,
+        contract_address: AccountId,
+        key: String,
+        value: String,
+        price: Balance,
+        quantity: u32,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Sells a NFT to a trait offer. NFT attribute is verified with the NFT contract.
+    #[ink(message)]
+    fn accept_trait_offer(&mut self, offer_id: u64, token_id: Id) -> Result<(), MarketplaceError>;
+
+    /// Gets a collection offer.
+    #[ink(message)]
+    fn get_collection_offer(&self, offer_id: u64) -> Option<CollectionOffer>;
+
+    /// Gets ids of all offers made for a collection.
+    #[ink(message)]
+    fn get_collection_offers(&self, contract_address: AccountId) -> Vec<u64>;
+
+    /// Sets min amount of an offer.
+    #[ink(message)]
+    fn set_min_offer_price(&mut self, min_offer_price: Balance) -> Result<(), MarketplaceError>;
+
+    /// Gets min amount of an offer.
+    #[ink(message)]
+    fn get_min_offer_price(&self) -> Balance;
+}
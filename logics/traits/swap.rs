@@ -0,0 +1,40 @@
+use crate::impls::marketplace::types::{
+    MarketplaceError,
+    Swap,
+    SwapTarget,
+};
+use ink::prelude::vec::Vec;
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{
+        AccountId,
+        Timestamp,
+    },
+};
+
+#[openbrush::trait_definition]
+pub trait MarketplaceSwap {
+    /// Creates an order to swap caller's NFTs for the target NFTs. Transferred value is
+    /// held by the marketplace as a top-up paid to the taker together with the NFTs.
+    /// Order can be accepted until `expiry`.
+    #[ink(message, payable)]
+    fn make_swap(
+        &mut self,
+        offered: Vec<(AccountId, Id)>,
+        target: SwapTarget,
+        expiry: Timestamp,
+    ) -> Result<u64, MarketplaceError>;
+
+    /// Cancels a swap order and credits the top-up to the maker's pending balance.
+    #[ink(message)]
+    fn cancel_swap(&mut self, swap_id: u64) -> Result<(), MarketplaceError>;
+
+    /// Executes a swap order, exchanging caller's target NFTs for the offered NFTs and top-up.
+    /// `token_id` of the caller's NFT must be given if the swap targets a whole collection.
+    #[ink(message)]
+    fn accept_swap(&mut self, swap_id: u64, token_id: Option<Id>) -> Result<(), MarketplaceError>;
+
+    /// Gets a swap order.
+    #[ink(message)]
+    fn get_swap(&self, swap_id: u64) -> Option<Swap>;
+}
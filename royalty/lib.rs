@@ -0,0 +1,86 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(min_specialization)]
+
+//! Royalty info standard for NFT contracts, an analogue of EIP-2981.
+//! Lets any marketplace query creator royalties directly from the NFT contract.
+
+use openbrush::{
+    contracts::psp34::Id,
+    traits::{
+        AccountId,
+        Balance,
+        Storage,
+    },
+};
+
+pub const STORAGE_KEY: u32 = openbrush::storage_unique_key!(Data);
+
+/// Maximum royalty in basis points, i.e. the whole sale price.
+pub const MAX_ROYALTY: u16 = 10_000;
+
+#[derive(Default, Debug)]
+#[openbrush::upgradeable_storage(STORAGE_KEY)]
+pub struct Data {
+    pub receiver: AccountId,
+    /// Royalty in basis points.
+    pub royalty: u16,
+}
+
+#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RoyaltyError {
+    /// Caller is not allowed to change royalty info.
+    NotOwner,
+    /// Royalty exceeds the sale price.
+    RoyaltyTooHigh,
+}
+
+#[openbrush::wrapper]
+pub type RoyaltyInfoRef = dyn RoyaltyInfo;
+
+#[openbrush::trait_definition]
+pub trait RoyaltyInfo {
+    /// Returns royalty receiver and royalty amount owed on a sale of the token for `sale_price`.
+    #[ink(message)]
+    fn royalty_info(&self, token_id: Id, sale_price: Balance) -> (AccountId, Balance);
+
+    /// Sets royalty receiver and royalty in basis points. Only the contract owner can call it.
+    #[ink(message)]
+    fn set_royalty_info(&mut self, receiver: AccountId, royalty: u16) -> Result<(), RoyaltyError>;
+}
+
+pub trait Internal {
+    /// Checks if the caller owns the contract. Implemented by the NFT contract,
+    /// which knows how its ownership is managed.
+    fn _check_royalty_owner(&self) -> Result<(), RoyaltyError>;
+}
+
+impl<T> RoyaltyInfo for T
+where
+    T: Storage<Data> + Internal,
+{
+    default fn royalty_info(&self, _token_id: Id, sale_price: Balance) -> (AccountId, Balance) {
+        let data = self.data::<Data>();
+        let royalty = sale_price
+            .checked_mul(data.royalty as u128)
+            .unwrap_or_default()
+            / 10_000;
+        (data.receiver, royalty)
+    }
+
+    default fn set_royalty_info(
+        &mut self,
+        receiver: AccountId,
+        royalty: u16,
+    ) -> Result<(), RoyaltyError> {
+        self._check_royalty_owner()?;
+        if royalty > MAX_ROYALTY {
+            return Err(RoyaltyError::RoyaltyTooHigh)
+        }
+
+        let data = self.data::<Data>();
+        data.receiver = receiver;
+        data.royalty = royalty;
+        Ok(())
+    }
+}
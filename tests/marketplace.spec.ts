@@ -3,12 +3,15 @@ import type { WeightV2, Weight } from '@polkadot/types/interfaces';
 import { expect, use } from 'chai';
 import chaiAsPromised from 'chai-as-promised';
 import { encodeAddress } from '@polkadot/keyring';
+import { blake2AsHex } from '@polkadot/util-crypto';
 import { FrameSystemAccountInfo } from '@polkadot/types/lookup';
 import BN from 'bn.js';
 import Market_factory from '../types/constructors/marketplace';
 import Market from '../types/contracts/marketplace';
 import TestPSP34_factory from '../types/constructors/test_psp34';
 import TestPSP34 from '../types/contracts/test_psp34';
+import TestPSP22_factory from '../types/constructors/test_psp22';
+import TestPSP22 from '../types/contracts/test_psp22';
 import Shiden34_Factory from '../types/constructors/shiden34';
 import Shiden34 from '../types/contracts/shiden34';
 import Rmrk_Factory from '../types/constructors/rmrk_equippable';
@@ -16,7 +19,7 @@ import Rmrk from '../types/contracts/rmrk_equippable';
 import { ApiPromise, WsProvider, Keyring } from '@polkadot/api';
 import { KeyringPair } from '@polkadot/keyring/types';
 import { ReturnNumber } from '@727-ventures/typechain-types';
-import { Hash, NftContractType } from '../types/types-arguments/marketplace';
+import { Hash, ListingOptions, NftContractType } from '../types/types-arguments/marketplace';
 
 use(chaiAsPromised);
 
@@ -29,6 +32,11 @@ const ONE = new BN(10).pow(new BN(18));
 const PRICE_PER_MINT = ONE;
 const MAX_REF_TIME = '500000000000';
 const MAX_PROOF_SIZE = '5242880';
+// Default mocha timeout of the test script.
+const TEST_TIMEOUT = 20000;
+// Duration of auction phases in ms. Kept short so the tests don't wait long,
+// can be raised with AUCTION_PERIOD env variable for nodes with slower blocks.
+const AUCTION_PERIOD = Number(process.env.AUCTION_PERIOD ?? 3000);
 
 // Create a new instance of contract
 const wsProvider = new WsProvider('ws://127.0.0.1:9944');
@@ -38,6 +46,7 @@ const keyring = new Keyring({ type: 'sr25519' });
 describe('Marketplace tests', () => {
   let marketplaceFactory: Market_factory;
   let psp34Factory: TestPSP34_factory;
+  let psp22Factory: TestPSP22_factory;
   let shiden34Factory: Shiden34_Factory;
   let rmrkFactory: Rmrk_Factory;
   let api: ApiPromise;
@@ -46,6 +55,7 @@ describe('Marketplace tests', () => {
   let charlie: KeyringPair;
   let marketplace: Market;
   let psp34: TestPSP34;
+  let psp22: TestPSP22;
   let shiden34: Shiden34;
   let rmrk: Rmrk;
 
@@ -62,16 +72,20 @@ describe('Marketplace tests', () => {
     charlie = keyring.addFromUri('//Charlie');
     marketplaceFactory = new Market_factory(api, deployer);
     psp34Factory = new TestPSP34_factory(api, deployer);
+    psp22Factory = new TestPSP22_factory(api, deployer);
     shiden34Factory = new Shiden34_Factory(api, deployer);
     rmrkFactory = new Rmrk_Factory(api, deployer);
     marketplace = new Market((await marketplaceFactory.new(deployer.address)).address, deployer, api);
     psp34 = new TestPSP34((await psp34Factory.new()).address, deployer, api);
+    psp22 = new TestPSP22((await psp22Factory.new(1000)).address, deployer, api);
     shiden34 = new Shiden34((await shiden34Factory.new(
       'default'.split(''),
       'DFT'.split(''),
       'uri'.split(''),
       1000,
-      1
+      1,
+      deployer.address,
+      100
     )).address, deployer, api);
     rmrk = new Rmrk((await rmrkFactory.new(
       'default'.split(''),
@@ -81,7 +95,7 @@ describe('Marketplace tests', () => {
       1,
       'meta'.split(''),
       deployer.address,
-      1
+      100
     )).address, deployer, api);
   }
 
@@ -96,10 +110,21 @@ describe('Marketplace tests', () => {
 
   it('setMarketplaceFee works', async () => {
     await setup();
-    let { gasRequired } = await marketplace.query.setMarketplaceFee(120);
-
-    let result = await marketplace.tx.setMarketplaceFee(120, { gasLimit: getEstimatedGas(gasRequired) });
-    expect((await marketplace.query.getMarketplaceFee()).value.unwrap()).to.equal(120);
+    let { gasRequired } = await marketplace.query.proposeMarketplaceFee(120);
+    const proposeResult = await marketplace.tx.proposeMarketplaceFee(120, { gasLimit: getEstimatedGas(gasRequired) });
+
+    // Fee increase waits for the fee change delay.
+    expect(proposeResult.events.find(event => event.name === 'FeeChangeProposed').args.fee).to.equal(120);
+    expect((await marketplace.query.getMarketplaceFee()).value.unwrap()).to.equal(100);
+    expect((await marketplace.query.getPendingMarketplaceFee()).value.unwrap()[0]).to.equal(120);
+    const executeResult = await marketplace.query.executeMarketplaceFee();
+    expect(executeResult.value.unwrap().err.hasOwnProperty('feeChangeNotReady')).to.be.true;
+
+    // Fee decrease is applied right away.
+    ({ gasRequired } = await marketplace.query.proposeMarketplaceFee(50));
+    const result = await marketplace.tx.proposeMarketplaceFee(50, { gasLimit: getEstimatedGas(gasRequired) });
+    checkIfEventIsEmitted(result, 'FeeChangeExecuted', { fee: 50, feeRecipient: null });
+    expect((await marketplace.query.getMarketplaceFee()).value.unwrap()).to.equal(50);
   })
 
   it('register contract works for the Marketplace owner', async () => {
@@ -108,7 +133,8 @@ describe('Marketplace tests', () => {
 
     const contract = await (await marketplace.query.getRegisteredCollection(psp34.address)).value.unwrap();
 
-    expect(contract.royaltyReceiver).to.be.equal(deployer.address);
+    expect(contract.royaltyReceivers[0][0]).to.be.equal(deployer.address);
+    expect(contract.royaltyReceivers[0][1]).to.be.equal(10000);
     expect(contract.royalty).to.be.equal(100);
     expect(contract.marketplaceIpfs).to.be.equal(toHex(string2ascii('ipfs')));
   });
@@ -117,16 +143,14 @@ describe('Marketplace tests', () => {
     await setup();
 
     const ipfs = string2ascii('ipfs');
-    const { gasRequired } = await marketplace.withSigner(deployer).query.register(psp34.address, deployer.address, 10001, ipfs);
-    const registerResult = await marketplace.withSigner(deployer).query.register(psp34.address, deployer.address, 10001, ipfs, { gasLimit: getEstimatedGas(gasRequired) });
+    const { gasRequired } = await marketplace.withSigner(deployer).query.register(psp34.address, [[deployer.address, 10000]], 10001, ipfs);
+    const registerResult = await marketplace.withSigner(deployer).query.register(psp34.address, [[deployer.address, 10000]], 10001, ipfs, { gasLimit: getEstimatedGas(gasRequired) });
 
     expect(registerResult.value.unwrap().err.hasOwnProperty('feeTooHigh')).to.be.true;
   });
 
   it('list / unlist works', async () => {
-    await setup();
-    await mintToken(bob);
-    await registerContract(deployer);
+    await setupWithToken(bob);
     
     // List token to the marketplace.
     await listToken(bob);
@@ -138,7 +162,7 @@ describe('Marketplace tests', () => {
     const { gasRequired } = await marketplace.withSigner(bob).query.unlist(psp34.address, {u64: 1});
     const unlistResult = await marketplace.withSigner(bob).tx.unlist(psp34.address, {u64: 1}, { gasLimit: getEstimatedGas(gasRequired) });
     expect(unlistResult.result?.isFinalized).to.be.true;
-    checkIfEventIsEmitted(unlistResult, 'TokenListed', { contract: psp34.address, id: {u64: 1}, price: null });
+    checkIfEventIsEmitted(unlistResult, 'TokenListed', { contract: psp34.address, id: {u64: 1}, price: null, currency: null, reservedFor: null });
     
     // Check if the token is actually unlisted.
     const price = await marketplace.query.getPrice(psp34.address, {u64: 1});
@@ -146,9 +170,7 @@ describe('Marketplace tests', () => {
   });
 
   it('list fails if not a nft owner', async () => {
-    await setup();
-    await mintToken(bob);
-    await registerContract(deployer);
+    await setupWithToken(bob);
     
     // Try to list token to the marketplace.
     const { gasRequired } = await marketplace.withSigner(charlie).query.list(psp34.address, {u64: 1}, 100);
@@ -158,11 +180,11 @@ describe('Marketplace tests', () => {
   });
 
   it('list fails if token is already listed', async () => {
-    await setup();
-    await mintToken(bob);
-    await registerContract(deployer);
+    await setupWithToken(bob);
     
     // List token to the marketplace.
+    // Marketplace has to be approved to transfer the token before listing.
+    await approveToken(bob);
     const { gasRequired } = await marketplace.withSigner(bob).query.list(psp34.address, {u64: 1}, 100);
     await marketplace.withSigner(bob).tx.list(psp34.address, {u64: 1}, 100, { gasLimit: getEstimatedGas(gasRequired) });
 
@@ -173,9 +195,7 @@ describe('Marketplace tests', () => {
   });
 
   it('unlist fails if token is not listed', async () => {
-    await setup();
-    await mintToken(bob);
-    await registerContract(deployer);
+    await setupWithToken(bob);
     
     // unlist token to the marketplace.
     const { gasRequired } = await marketplace.withSigner(bob).query.unlist(psp34.address, {u64: 1});
@@ -185,38 +205,33 @@ describe('Marketplace tests', () => {
   });
 
   it('buy works', async () => {
-    await setup();
-    await mintToken(charlie);
-    await registerContract(deployer);
+    await setupWithToken(charlie);
     await listToken(charlie);
 
     // Charlie approves marketplace to be operator of the token
-    const approveGas = (await psp34.withSigner(charlie).query.approve(marketplace.address, { u64: 1 }, true)).gasRequired;
-    let approveResult = await psp34.withSigner(charlie).tx.approve(marketplace.address, { u64: 1 }, true, { gasLimit: getEstimatedGas(approveGas) });
-
-    const deployerOriginalBalance = await getBalance(deployer);
-    const bobOriginalBalance = await getBalance(bob);
-    const charlieOriginalBalance = await getBalance(charlie);
+    await approveToken(charlie);
 
     // Buy token
-    const { gasRequired } = await marketplace.withSigner(bob).query.buy(psp34.address, {u64: 1});
+    const { gasRequired } = await marketplace.withSigner(bob).query.buy(psp34.address, {u64: 1}, null);
     const buyResult = await marketplace.withSigner(bob).tx.buy(
       psp34.address, 
       {u64: 1},
+      null,
       { gasLimit: getEstimatedGas(gasRequired), value: new BN('100000000000000000000') });
 
     expect(buyResult.result?.isFinalized).to.be.true;
-    checkIfEventIsEmitted(buyResult, 'TokenBought', { contract: psp34.address, id: {u64: 1}, price: BigInt('100000000000000000000') })
-
-    // Balances check.
-    const deployerBalance = await getBalance(deployer);
-    const bobBalance = await getBalance(bob);
-    const charlieBalance = await getBalance(charlie);
-    
-    // Check the marketplace fee receiver balance. ATM all royalties go to deployer.
-    expect(deployerBalance.eq(deployerOriginalBalance.add(new BN('2000000000000000000')))).to.be.true;
-    // Check seller's balance. Should be increased by price - fees
-    expect(charlieBalance.toString()).to.be.equal(charlieOriginalBalance.add(new BN('98000000000000000000')).toString());
+    checkIfEventIsEmitted(buyResult, 'TokenBought', { contract: psp34.address, id: {u64: 1}, price: BigInt(100), currency: null })
+
+    // Pending balances check.
+    const deployerPending = (await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber;
+    const charliePending = (await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber;
+
+    // Check the marketplace fee receiver pending balance. ATM all royalties go to deployer.
+    expect(deployerPending.toString()).to.be.equal('2');
+    // Check seller's pending balance. Should be increased by price - fees
+    expect(charliePending.toString()).to.be.equal('98');
+    // Value above the price is credited to the buyer's pending balance.
+    expect((await marketplace.query.pendingBalance(bob.address)).value.unwrap().rawNumber.toString()).to.be.equal('99999999999999999900');
     // Check the token owner.
     expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
     // Check if allowance is unset.
@@ -226,8 +241,28 @@ describe('Marketplace tests', () => {
     const reBuyResult = await marketplace.withSigner(bob).query.buy(
       psp34.address, 
       {u64: 1},
+      null,
       { gasLimit: getEstimatedGas(gasRequired), value: new BN('100000000000000000000') });
-    expect(reBuyResult.value.unwrap().err.hasOwnProperty('alreadyOwner')).to.be.true;
+    expect(reBuyResult.value.unwrap().err.hasOwnProperty('itemNotListedForSale')).to.be.true;
+  });
+
+  it('buy charges exactly the price', async () => {
+    await setupWithToken(charlie);
+    await listToken(charlie);
+
+    // Value below the price is rejected.
+    const underpaidResult = await marketplace.withSigner(bob).query.buy(psp34.address, {u64: 1}, null, { value: 99 });
+    expect(underpaidResult.value.unwrap().err.hasOwnProperty('badBuyValue')).to.be.true;
+
+    // Buy token paying exactly the price.
+    const { gasRequired } = await marketplace.withSigner(bob).query.buy(psp34.address, {u64: 1}, null, { value: 100 });
+    const buyResult = await marketplace.withSigner(bob).tx.buy(psp34.address, {u64: 1}, null, { gasLimit: getEstimatedGas(gasRequired), value: 100 });
+    expect(buyResult.result?.isFinalized).to.be.true;
+
+    // Nothing is left over for the buyer.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    expect((await marketplace.query.pendingBalance(bob.address)).value.unwrap().rawNumber.toString()).to.be.equal('0');
   });
 
   it('buy RMRK works', async () => {
@@ -240,29 +275,25 @@ describe('Marketplace tests', () => {
     const approveGas = (await rmrk.withSigner(charlie).query.approve(marketplace.address, { u64: 1 }, true)).gasRequired;
     let approveResult = await rmrk.withSigner(charlie).tx.approve(marketplace.address, { u64: 1 }, true, { gasLimit: getEstimatedGas(approveGas) });
 
-    const deployerOriginalBalance = await getBalance(deployer);
-    const bobOriginalBalance = await getBalance(bob);
-    const charlieOriginalBalance = await getBalance(charlie);
-
     // Buy token
-    const { gasRequired, value } = await marketplace.withSigner(bob).query.buy(rmrk.address, {u64: 1}, { value: new BN('100000000000000000000') });
+    const { gasRequired, value } = await marketplace.withSigner(bob).query.buy(rmrk.address, {u64: 1}, null, { value: new BN('100000000000000000000') });
     const buyResult = await marketplace.withSigner(bob).tx.buy(
       rmrk.address, 
       {u64: 1},
+      null,
       { gasLimit: getEstimatedGas(gasRequired), value: new BN('100000000000000000000') });
 
     expect(buyResult.result?.isFinalized).to.be.true;
-    checkIfEventIsEmitted(buyResult, 'TokenBought', { contract: rmrk.address, id: {u64: 1}, price: BigInt('100000000000000000000') });
+    checkIfEventIsEmitted(buyResult, 'TokenBought', { contract: rmrk.address, id: {u64: 1}, price: BigInt(100), currency: null });
 
-    // Balances check.
-    const deployerBalance = await getBalance(deployer);
-    const bobBalance = await getBalance(bob);
-    const charlieBalance = await getBalance(charlie);
-    
-    // Check the marketplace fee receiver balance. ATM all royalties go to deployer.
-    expect(deployerBalance.eq(deployerOriginalBalance.add(new BN('2000000000000000000')))).to.be.true;
-    // Check seller's balance. Should be increased by price - fees
-    expect(charlieBalance.toString()).to.be.equal(charlieOriginalBalance.add(new BN('98000000000000000000')).toString());
+    // Pending balances check.
+    const deployerPending = (await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber;
+    const charliePending = (await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber;
+
+    // Check the marketplace fee receiver pending balance. ATM all royalties go to deployer.
+    expect(deployerPending.toString()).to.be.equal('2');
+    // Check seller's pending balance. Should be increased by price - fees
+    expect(charliePending.toString()).to.be.equal('98');
     // Check the token owner.
     expect((await rmrk.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
     // Check if allowance is unset.
@@ -272,8 +303,285 @@ describe('Marketplace tests', () => {
     const reBuyResult = await marketplace.withSigner(bob).query.buy(
       rmrk.address, 
       {u64: 1},
+      null,
       { gasLimit: getEstimatedGas(gasRequired), value: new BN('100000000000000000000') });
-    expect(reBuyResult.value.unwrap().err.hasOwnProperty('alreadyOwner')).to.be.true;
+    expect(reBuyResult.value.unwrap().err.hasOwnProperty('itemNotListedForSale')).to.be.true;
+  });
+
+  it('buy batch removes sold items', async () => {
+    await setupWithToken(charlie);
+    await listToken(charlie);
+
+    // Buy token in a batch
+    const items: [string, {u64: number}][] = [[psp34.address, {u64: 1}]];
+    const { gasRequired } = await marketplace.withSigner(bob).query.buyBatch(items, true, { value: 100 });
+    const buyResult = await marketplace.withSigner(bob).tx.buyBatch(items, true, { gasLimit: getEstimatedGas(gasRequired), value: 100 });
+    expect(buyResult.result?.isFinalized).to.be.true;
+
+    // Sold item is removed, so the buyer is able to list the token again.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.getListing(psp34.address, {u64: 1})).value.unwrap()).to.equal(null);
+    await listToken(bob);
+  });
+
+  it('escrow buy works', async () => {
+    await setupWithToken(charlie);
+
+    // List token in escrow, marketplace takes the token over.
+    await listTokenWithOptions(charlie, { startsAt: null, expiresAt: null, currency: null, escrow: true, reservedFor: null });
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(marketplace.address);
+    expect((await marketplace.query.getListing(psp34.address, {u64: 1})).value.unwrap().escrowed).to.be.true;
+
+    // Buy token
+    const { gasRequired } = await marketplace.withSigner(bob).query.buy(psp34.address, {u64: 1}, null, { value: 100 });
+    const buyResult = await marketplace.withSigner(bob).tx.buy(
+      psp34.address,
+      {u64: 1},
+      null,
+      { gasLimit: getEstimatedGas(gasRequired), value: 100 });
+
+    expect(buyResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(buyResult, 'TokenBought', { contract: psp34.address, id: {u64: 1}, price: BigInt(100), currency: null });
+
+    // Check the token owner and the seller's pending balance.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    // Sold item is removed, so the buyer is able to list the token again.
+    expect((await marketplace.query.getListing(psp34.address, {u64: 1})).value.unwrap()).to.equal(null);
+    await listToken(bob);
+  });
+
+  it('escrow unlist returns token', async () => {
+    await setupWithToken(charlie);
+    await listTokenWithOptions(charlie, { startsAt: null, expiresAt: null, currency: null, escrow: true, reservedFor: null });
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(marketplace.address);
+
+    // Unlist token from the marketplace.
+    const { gasRequired } = await marketplace.withSigner(charlie).query.unlist(psp34.address, {u64: 1});
+    const unlistResult = await marketplace.withSigner(charlie).tx.unlist(psp34.address, {u64: 1}, { gasLimit: getEstimatedGas(gasRequired) });
+    expect(unlistResult.result?.isFinalized).to.be.true;
+
+    // Check if the token is returned to the seller and unlisted.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(charlie.address);
+    expect((await marketplace.query.getPrice(psp34.address, {u64: 1})).value.ok).to.equal(null);
+  });
+
+  it('buy with PSP22 works', async () => {
+    await setupWithToken(charlie);
+
+    // Allow PSP22 token as a listing currency.
+    const currencyGas = (await marketplace.withSigner(deployer).query.addCurrency(psp22.address)).gasRequired;
+    await marketplace.withSigner(deployer).tx.addCurrency(psp22.address, { gasLimit: getEstimatedGas(currencyGas) });
+    await listTokenWithOptions(charlie, { startsAt: null, expiresAt: null, currency: psp22.address, escrow: false, reservedFor: null });
+
+    // Bob gets PSP22 tokens and approves marketplace to spend the price.
+    const transferGas = (await psp22.withSigner(deployer).query.transfer(bob.address, 100, [])).gasRequired;
+    await psp22.withSigner(deployer).tx.transfer(bob.address, 100, [], { gasLimit: getEstimatedGas(transferGas) });
+    const approveGas = (await psp22.withSigner(bob).query.approve(marketplace.address, 100)).gasRequired;
+    await psp22.withSigner(bob).tx.approve(marketplace.address, 100, { gasLimit: getEstimatedGas(approveGas) });
+
+    // Buy token, no native value is transferred.
+    const { gasRequired } = await marketplace.withSigner(bob).query.buy(psp34.address, {u64: 1}, null);
+    const buyResult = await marketplace.withSigner(bob).tx.buy(psp34.address, {u64: 1}, null, { gasLimit: getEstimatedGas(gasRequired) });
+
+    expect(buyResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(buyResult, 'TokenBought', { contract: psp34.address, id: {u64: 1}, price: BigInt(100), currency: psp22.address });
+
+    // Check the token owner and PSP22 balances. Marketplace fee and royalty go to deployer.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await psp22.query.balanceOf(bob.address)).value.unwrap().rawNumber.toString()).to.be.equal('0');
+    expect((await psp22.query.balanceOf(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    expect((await psp22.query.balanceOf(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('902');
+  });
+
+  it('auction settle works', async () => {
+    await setupWithToken(charlie);
+
+    // Charlie approves marketplace and puts the token up for an auction.
+    await approveToken(charlie);
+    const now = (await api.query.timestamp.now()).toNumber();
+    const endTime = now + AUCTION_PERIOD;
+    const auctionGas = (await marketplace.withSigner(charlie).query.createAuction(psp34.address, {u64: 1}, 100, now, endTime)).gasRequired;
+    await marketplace.withSigner(charlie).tx.createAuction(psp34.address, {u64: 1}, 100, now, endTime, { gasLimit: getEstimatedGas(auctionGas) });
+
+    // Bob places a bid.
+    const bidGas = (await marketplace.withSigner(bob).query.placeBid(psp34.address, {u64: 1}, null, { value: 100 })).gasRequired;
+    const bidResult = await marketplace.withSigner(bob).tx.placeBid(psp34.address, {u64: 1}, null, { gasLimit: getEstimatedGas(bidGas), value: 100 });
+    expect(bidResult.result?.isFinalized).to.be.true;
+    expect((await marketplace.query.getAuction(psp34.address, {u64: 1})).value.unwrap().highestBidder).to.equal(bob.address);
+
+    // Settle the auction once it has ended.
+    await waitUntil(endTime);
+    const settleGas = (await marketplace.withSigner(charlie).query.settleAuction(psp34.address, {u64: 1})).gasRequired;
+    const settleResult = await marketplace.withSigner(charlie).tx.settleAuction(psp34.address, {u64: 1}, { gasLimit: getEstimatedGas(settleGas) });
+    expect(settleResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(settleResult, 'AuctionSettled', { contract: psp34.address, id: {u64: 1}, winner: bob.address, price: BigInt(100) });
+
+    // Check the token owner and pending balances.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('2');
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    expect((await marketplace.query.getAuction(psp34.address, {u64: 1})).value.unwrap()).to.equal(null);
+  }).timeout(TEST_TIMEOUT + AUCTION_PERIOD);
+
+  it('accept offer works', async () => {
+    await setupWithToken(charlie);
+
+    // Bob makes an offer for Charlie's token.
+    const expiry = (await api.query.timestamp.now()).toNumber() + 60000;
+    const offerGas = (await marketplace.withSigner(bob).query.makeOffer(psp34.address, {u64: 1}, expiry, { value: 100 })).gasRequired;
+    const offerResult = await marketplace.withSigner(bob).tx.makeOffer(psp34.address, {u64: 1}, expiry, { gasLimit: getEstimatedGas(offerGas), value: 100 });
+    expect(offerResult.result?.isFinalized).to.be.true;
+    const offerId = (await marketplace.query.getOffers(psp34.address, {u64: 1})).value.unwrap()[0];
+
+    // Charlie approves marketplace and accepts the offer.
+    await approveToken(charlie);
+    const { gasRequired } = await marketplace.withSigner(charlie).query.acceptOffer(offerId);
+    const acceptResult = await marketplace.withSigner(charlie).tx.acceptOffer(offerId, { gasLimit: getEstimatedGas(gasRequired) });
+    expect(acceptResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(acceptResult, 'OfferAccepted', { offerId, seller: charlie.address });
+
+    // Check the token owner and pending balances.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('2');
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    expect((await marketplace.query.getOffers(psp34.address, {u64: 1})).value.unwrap()).to.be.empty;
+  });
+
+  it('buy bundle works', async () => {
+    await setupWithToken(charlie);
+    const mintGas = (await psp34.withSigner(charlie).query.mint(charlie.address, {u64: 2})).gasRequired;
+    await psp34.withSigner(charlie).tx.mint(charlie.address, {u64: 2}, { gasLimit: getEstimatedGas(mintGas) });
+
+    // Charlie approves marketplace and lists both tokens as a bundle.
+    for (const id of [1, 2]) {
+      await approveToken(charlie, id);
+    }
+    const tokens: [string, {u64: number}, number][] = [[psp34.address, {u64: 1}, 1], [psp34.address, {u64: 2}, 1]];
+    const listGas = (await marketplace.withSigner(charlie).query.listBundle(tokens, 200)).gasRequired;
+    await marketplace.withSigner(charlie).tx.listBundle(tokens, 200, { gasLimit: getEstimatedGas(listGas) });
+    const bundleId = (await marketplace.query.getTokenBundle(psp34.address, {u64: 2})).value.unwrap();
+
+    // Value below the bundle price is rejected.
+    const underpaidResult = await marketplace.withSigner(bob).query.buyBundle(bundleId, { value: 199 });
+    expect(underpaidResult.value.unwrap().err.hasOwnProperty('badBuyValue')).to.be.true;
+
+    // Buy bundle paying more than the price.
+    const { gasRequired } = await marketplace.withSigner(bob).query.buyBundle(bundleId, { value: 250 });
+    const buyResult = await marketplace.withSigner(bob).tx.buyBundle(bundleId, { gasLimit: getEstimatedGas(gasRequired), value: 250 });
+    expect(buyResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(buyResult, 'BundleBought', { bundleId, buyer: bob.address, price: BigInt(200) });
+
+    // Check the token owners and pending balances. Price is split evenly between the tokens.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await psp34.query.ownerOf({ u64: 2 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('4');
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('196');
+    // Value above the price is credited to the buyer.
+    expect((await marketplace.query.pendingBalance(bob.address)).value.unwrap().rawNumber.toString()).to.be.equal('50');
+    expect((await marketplace.query.getBundle(bundleId)).value.unwrap()).to.equal(null);
+  });
+
+  it('accept swap works', async () => {
+    await setupWithToken(charlie);
+    await mintRmrkToken(bob);
+
+    // Bob offers his RMRK token and a top-up for Charlie's token.
+    const rmrkApproveGas = (await rmrk.withSigner(bob).query.approve(marketplace.address, { u64: 1 }, true)).gasRequired;
+    await rmrk.withSigner(bob).tx.approve(marketplace.address, { u64: 1 }, true, { gasLimit: getEstimatedGas(rmrkApproveGas) });
+    const offered: [string, {u64: number}][] = [[rmrk.address, {u64: 1}]];
+    const target = { tokens: [[psp34.address, {u64: 1}]] as [string, {u64: number}][] };
+    const expiry = (await api.query.timestamp.now()).toNumber() + 60000;
+    const swapGas = (await marketplace.withSigner(bob).query.makeSwap(offered, target, expiry, { value: 100 })).gasRequired;
+    const swapResult = await marketplace.withSigner(bob).tx.makeSwap(offered, target, expiry, { gasLimit: getEstimatedGas(swapGas), value: 100 });
+    expect(swapResult.result?.isFinalized).to.be.true;
+    const swapId = swapResult.events.find(event => event.name === 'SwapMade').args.swapId;
+
+    // Charlie approves marketplace and accepts the swap.
+    await approveToken(charlie);
+    const { gasRequired } = await marketplace.withSigner(charlie).query.acceptSwap(swapId, null);
+    const acceptResult = await marketplace.withSigner(charlie).tx.acceptSwap(swapId, null, { gasLimit: getEstimatedGas(gasRequired) });
+    expect(acceptResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(acceptResult, 'SwapAccepted', { swapId, taker: charlie.address });
+
+    // Check the token owners and pending balances. Fees are charged on the top-up.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await rmrk.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(charlie.address);
+    expect((await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('2');
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    expect((await marketplace.query.getSwap(swapId)).value.unwrap()).to.equal(null);
+  });
+
+  it('sealed auction settle works', async () => {
+    await setupWithToken(charlie);
+
+    // Charlie approves marketplace and puts the token up for a sealed-bid auction.
+    await approveToken(charlie);
+    const now = (await api.query.timestamp.now()).toNumber();
+    const commitEnd = now + AUCTION_PERIOD;
+    const revealEnd = commitEnd + AUCTION_PERIOD;
+    const auctionGas = (await marketplace.withSigner(charlie).query.createSealedAuction(psp34.address, {u64: 1}, 100, commitEnd, revealEnd, false)).gasRequired;
+    const auctionResult = await marketplace.withSigner(charlie).tx.createSealedAuction(psp34.address, {u64: 1}, 100, commitEnd, revealEnd, false, { gasLimit: getEstimatedGas(auctionGas) });
+    const auctionId = auctionResult.events.find(event => event.name === 'SealedAuctionCreated').args.auctionId;
+
+    // Bob commits a bid of 100 with a deposit of 150.
+    const salt = '0x' + '01'.repeat(32);
+    const commitment = blake2AsHex(api.createType('(AccountId, Balance, Hash)', [bob.address, 100, salt]).toU8a(), 256);
+    const commitGas = (await marketplace.withSigner(bob).query.commitSealedBid(auctionId, commitment, null, { value: 150 })).gasRequired;
+    const commitResult = await marketplace.withSigner(bob).tx.commitSealedBid(auctionId, commitment, null, { gasLimit: getEstimatedGas(commitGas), value: 150 });
+    expect(commitResult.result?.isFinalized).to.be.true;
+
+    // Bob reveals the bid once the commit phase has ended.
+    await waitUntil(commitEnd);
+    const revealGas = (await marketplace.withSigner(bob).query.revealSealedBid(auctionId, 100, salt)).gasRequired;
+    const revealResult = await marketplace.withSigner(bob).tx.revealSealedBid(auctionId, 100, salt, { gasLimit: getEstimatedGas(revealGas) });
+    checkIfEventIsEmitted(revealResult, 'SealedBidRevealed', { auctionId, bidder: bob.address, bid: BigInt(100) });
+
+    // Settle the auction once the reveal phase has ended.
+    await waitUntil(revealEnd);
+    const settleGas = (await marketplace.withSigner(charlie).query.settleSealedAuction(auctionId)).gasRequired;
+    const settleResult = await marketplace.withSigner(charlie).tx.settleSealedAuction(auctionId, { gasLimit: getEstimatedGas(settleGas) });
+    expect(settleResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(settleResult, 'AuctionSettled', { contract: psp34.address, id: {u64: 1}, winner: bob.address, price: BigInt(100) });
+
+    // Check the token owner and pending balances. Deposit above the bid is returned to Bob.
+    expect((await psp34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('2');
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('98');
+    expect((await marketplace.query.pendingBalance(bob.address)).value.unwrap().rawNumber.toString()).to.be.equal('50');
+  }).timeout(TEST_TIMEOUT + 2 * AUCTION_PERIOD);
+
+  it('buy pays royalty reported by the NFT contract', async () => {
+    await setup();
+
+    // Charlie mints Shiden34 token, which reports 1% royalty for deployer.
+    const mintGas = (await shiden34.withSigner(charlie).query.mintNext({ value: 1 })).gasRequired;
+    await shiden34.withSigner(charlie).tx.mintNext({ gasLimit: getEstimatedGas(mintGas), value: 1 });
+    const royaltyInfo = (await shiden34.query.royaltyInfo({u64: 1}, 1000)).value.unwrap();
+    expect(royaltyInfo[0]).to.equal(deployer.address);
+    expect(royaltyInfo[1].rawNumber.toString()).to.be.equal('10');
+
+    // Collection is registered with a single royalty receiver, so the NFT royalty is preferred.
+    const ipfs = string2ascii('ipfs');
+    const registerGas = (await marketplace.withSigner(deployer).query.register(shiden34.address, [[bob.address, 10000]], 500, ipfs)).gasRequired;
+    await marketplace.withSigner(deployer).tx.register(shiden34.address, [[bob.address, 10000]], 500, ipfs, { gasLimit: getEstimatedGas(registerGas) });
+
+    // Charlie approves marketplace and lists the token.
+    const approveGas = (await shiden34.withSigner(charlie).query.approve(marketplace.address, { u64: 1 }, true)).gasRequired;
+    await shiden34.withSigner(charlie).tx.approve(marketplace.address, { u64: 1 }, true, { gasLimit: getEstimatedGas(approveGas) });
+    const listGas = (await marketplace.withSigner(charlie).query.list(shiden34.address, {u64: 1}, 1000)).gasRequired;
+    await marketplace.withSigner(charlie).tx.list(shiden34.address, {u64: 1}, 1000, { gasLimit: getEstimatedGas(listGas) });
+
+    // Buy token
+    const { gasRequired } = await marketplace.withSigner(bob).query.buy(shiden34.address, {u64: 1}, null, { value: 1000 });
+    const buyResult = await marketplace.withSigner(bob).tx.buy(shiden34.address, {u64: 1}, null, { gasLimit: getEstimatedGas(gasRequired), value: 1000 });
+    expect(buyResult.result?.isFinalized).to.be.true;
+
+    // Deployer gets the marketplace fee and the NFT royalty, registered receiver gets nothing.
+    expect((await shiden34.query.ownerOf({ u64: 1 })).value.unwrap()).to.equal(bob.address);
+    expect((await marketplace.query.pendingBalance(deployer.address)).value.unwrap().rawNumber.toString()).to.be.equal('20');
+    expect((await marketplace.query.pendingBalance(charlie.address)).value.unwrap().rawNumber.toString()).to.be.equal('980');
+    expect((await marketplace.query.pendingBalance(bob.address)).value.unwrap().rawNumber.toString()).to.be.equal('0');
   });
 
   it('setContractMetadata works', async () => {
@@ -340,7 +648,7 @@ describe('Marketplace tests', () => {
     
     const gas = (await marketplace.withSigner(deployer).query.factory(
       string2ascii(marketplace_ipfs),
-      bob.address,
+      [[bob.address, 6000], [charlie.address, 4000]],
       200,
       string2ascii('testNft'),
       string2ascii('TST'),
@@ -351,7 +659,7 @@ describe('Marketplace tests', () => {
     )).gasRequired;
     const factoryResult = await marketplace.withSigner(deployer).tx.factory(
       string2ascii(marketplace_ipfs),
-      bob.address,
+      [[bob.address, 6000], [charlie.address, 4000]],
       200,
       string2ascii('testNft'),
       string2ascii('TST'),
@@ -373,10 +681,30 @@ describe('Marketplace tests', () => {
     const registerCheckResult = await (await marketplace.query.getRegisteredCollection(shiden34Address)).value.unwrap();
     expect(registerCheckResult).is.not.empty;
     expect(registerCheckResult.royalty).to.be.equal(200);
-    expect(registerCheckResult.royaltyReceiver).to.be.equal(bob.address);
+    expect(registerCheckResult.royaltyReceivers[0][0]).to.be.equal(bob.address);
+    expect(registerCheckResult.royaltyReceivers[1][0]).to.be.equal(charlie.address);
     expect(registerCheckResult.marketplaceIpfs).to.be.equal(toHex(string2ascii(marketplace_ipfs)));
   }
 
+  // Helper function to deploy contracts, mint a token for the seller and register the collection.
+  async function setupWithToken(seller: KeyringPair): Promise<void> {
+    await setup();
+    await mintToken(seller);
+    await registerContract(deployer);
+  }
+
+  // Helper function to approve marketplace to transfer the token.
+  async function approveToken(signer: KeyringPair, id: number = 1): Promise<void> {
+    const { gasRequired } = await psp34.withSigner(signer).query.approve(marketplace.address, { u64: id }, true);
+    await psp34.withSigner(signer).tx.approve(marketplace.address, { u64: id }, true, { gasLimit: getEstimatedGas(gasRequired) });
+  }
+
+  // Helper function to wait until chain time passes the timestamp.
+  async function waitUntil(timestamp: number): Promise<void> {
+    const now = (await api.query.timestamp.now()).toNumber();
+    await sleep(timestamp - now);
+  }
+
   // Helper function to mint a token.
   async function mintToken(signer: KeyringPair): Promise<void> {
     const { gasRequired } = await psp34.withSigner(signer).query.mint(signer.address, {u64: 1});
@@ -399,8 +727,8 @@ describe('Marketplace tests', () => {
   // Helper function to register contract.
   async function registerContract(signer:KeyringPair) {
     const ipfs = string2ascii('ipfs');
-    const { gasRequired } = await marketplace.withSigner(signer).query.register(psp34.address, signer.address, 100, ipfs);
-    const registerResult = await marketplace.withSigner(signer).tx.register(psp34.address, signer.address, 100, ipfs, { gasLimit: getEstimatedGas(gasRequired) });
+    const { gasRequired } = await marketplace.withSigner(signer).query.register(psp34.address, [[signer.address, 10000]], 100, ipfs);
+    const registerResult = await marketplace.withSigner(signer).tx.register(psp34.address, [[signer.address, 10000]], 100, ipfs, { gasLimit: getEstimatedGas(gasRequired) });
     expect(registerResult.result?.isFinalized).to.be.true;
     checkIfEventIsEmitted(registerResult, 'CollectionRegistered', { contract: psp34.address });
   }
@@ -408,26 +736,40 @@ describe('Marketplace tests', () => {
     // Helper function to register RMRK contract.
     async function registerRmrkContract(signer:KeyringPair) {
       const ipfs = string2ascii('ipfs');
-      const { gasRequired } = await marketplace.withSigner(signer).query.register(rmrk.address, signer.address, 100, ipfs);
-      const registerResult = await marketplace.withSigner(signer).tx.register(rmrk.address, signer.address, 100, ipfs, { gasLimit: getEstimatedGas(gasRequired) });
+      const { gasRequired } = await marketplace.withSigner(signer).query.register(rmrk.address, [[signer.address, 10000]], 100, ipfs);
+      const registerResult = await marketplace.withSigner(signer).tx.register(rmrk.address, [[signer.address, 10000]], 100, ipfs, { gasLimit: getEstimatedGas(gasRequired) });
       expect(registerResult.result?.isFinalized).to.be.true;
       checkIfEventIsEmitted(registerResult, 'CollectionRegistered', { contract: rmrk.address });
     }
 
   // Helper function to list token for sale.
   async function listToken(signer:KeyringPair) {
+    // Marketplace has to be approved to transfer the token before listing.
+    await approveToken(signer);
     const { gasRequired } = await marketplace.withSigner(signer).query.list(psp34.address, {u64: 1}, 100);
     const listResult = await marketplace.withSigner(signer).tx.list(psp34.address, {u64: 1}, 100, { gasLimit: getEstimatedGas(gasRequired) });
     expect(listResult.result?.isFinalized).to.be.true;
-    checkIfEventIsEmitted(listResult, 'TokenListed', { contract: psp34.address, id: {u64: 1}, price: 100 });
+    checkIfEventIsEmitted(listResult, 'TokenListed', { contract: psp34.address, id: {u64: 1}, price: 100, currency: null, reservedFor: null });
+  }
+
+  // Helper function to list token for sale with listing options.
+  async function listTokenWithOptions(signer:KeyringPair, options: ListingOptions) {
+    await approveToken(signer);
+    const { gasRequired } = await marketplace.withSigner(signer).query.listWithOptions(psp34.address, {u64: 1}, 100, options);
+    const listResult = await marketplace.withSigner(signer).tx.listWithOptions(psp34.address, {u64: 1}, 100, options, { gasLimit: getEstimatedGas(gasRequired) });
+    expect(listResult.result?.isFinalized).to.be.true;
+    checkIfEventIsEmitted(listResult, 'TokenListed', { contract: psp34.address, id: {u64: 1}, price: 100, currency: options.currency, reservedFor: options.reservedFor });
   }
 
   // Helper function to list RMRK token for sale.
   async function listRmrkToken(signer:KeyringPair) {
+    // Marketplace has to be approved to transfer the token before listing.
+    const approveGas = (await rmrk.withSigner(signer).query.approve(marketplace.address, { u64: 1 }, true)).gasRequired;
+    await rmrk.withSigner(signer).tx.approve(marketplace.address, { u64: 1 }, true, { gasLimit: getEstimatedGas(approveGas) });
     const { gasRequired } = await marketplace.withSigner(signer).query.list(rmrk.address, {u64: 1}, 100);
     const listResult = await marketplace.withSigner(signer).tx.list(rmrk.address, {u64: 1}, 100, { gasLimit: getEstimatedGas(gasRequired) });
     expect(listResult.result?.isFinalized).to.be.true;
-    checkIfEventIsEmitted(listResult, 'TokenListed', { contract: rmrk.address, id: {u64: 1}, price: 100 });
+    checkIfEventIsEmitted(listResult, 'TokenListed', { contract: rmrk.address, id: {u64: 1}, price: 100, currency: null, reservedFor: null });
   }
 
   // Helper function to get account balance
@@ -465,6 +807,11 @@ function checkIfEventIsEmitted(result: { events?: any }, name: string, args: any
   expect(event).eql({ name, args, });
 }
 
+// Helper function to wait for chain time to pass.
+function sleep(ms: number): Promise<void> {
+  return new Promise(resolve => setTimeout(resolve, ms));
+}
+
 // Helper function to get ASCII array from string.
 function string2ascii(inputString: string): number[] {
   let result: number[] = [];